@@ -13,10 +13,13 @@ use std::{
 use tui::{backend::CrosstermBackend, Terminal};
 
 use crate::{
+    backend::{TaskError, TaskManager},
     frontend::{
         core::{Logic, Menu, MenuEvent, UIContext},
         menus::MainMenu,
     },
+    hooks::HookRunner,
+    workspace::Workspace,
 };
 
 pub type ApplicationBackend = tui::backend::CrosstermBackend<Stdout>;
@@ -26,9 +29,9 @@ pub struct Application {
 }
 
 impl Application {
-    pub fn new() -> Result<Application, io::Error> {
+    pub fn new(task_manager: TaskManager, workspace: Workspace, hook_runner: HookRunner, load_error: Option<TaskError>) -> Result<Application, io::Error> {
         Ok(Application {
-            logic: Rc::new(RefCell::new(Logic::new())),
+            logic: Rc::new(RefCell::new(Logic::new(task_manager, workspace, hook_runner, load_error))),
         })
     }
 
@@ -0,0 +1,67 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
+
+/// Path of the backup with the given index, `1` being the most recent one.
+pub fn backup_path(file_path: &str, index: usize) -> String {
+    format!("{}.{}", file_path, index)
+}
+
+/// Returns the indices of the backups of `file_path` that exist, most recent first.
+pub fn list_backups(file_path: &str) -> Vec<usize> {
+    (1..)
+        .take_while(|index| Path::new(&backup_path(file_path, *index)).exists())
+        .collect()
+}
+
+/// Replaces `file_path` with `content` so that a crash leaves either the old or the new version on disk.
+/// The previous version becomes backup `1`, shifting the others and dropping the ones over `backup_count`.
+pub fn write_atomically(file_path: &str, content: &[u8], backup_count: usize) -> io::Result<()> {
+    let temp_path = format!("{}.tmp", file_path);
+    {
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(content)?;
+        temp_file.sync_all()?;
+    }
+
+    if let Err(error) = rotate_backups(file_path, backup_count) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error);
+    }
+    fs::rename(&temp_path, file_path)?;
+    sync_parent_directory(file_path);
+    Ok(())
+}
+
+fn rotate_backups(file_path: &str, backup_count: usize) -> io::Result<()> {
+    if backup_count == 0 || !Path::new(file_path).exists() {
+        return Ok(());
+    }
+    for index in (1..backup_count).rev() {
+        let current_path = backup_path(file_path, index);
+        if Path::new(&current_path).exists() {
+            fs::rename(&current_path, backup_path(file_path, index + 1))?;
+        }
+    }
+    fs::copy(file_path, backup_path(file_path, 1))?;
+    Ok(())
+}
+
+//Makes the rename itself durable, not possible on every platform so errors are ignored
+#[cfg(unix)]
+fn sync_parent_directory(file_path: &str) {
+    let parent = match Path::new(file_path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if let Ok(directory) = File::open(parent) {
+        let _ = directory.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_directory(_file_path: &str) {}
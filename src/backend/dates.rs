@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+/// Tasks due within this time are due soon.
+pub const DUE_SOON_HOURS: i64 = 24;
+
+/// Time given to due dates written without one, the end of the day.
+pub fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 0).unwrap()
+}
+
+/// Time given to start dates written without one, the start of the day.
+pub fn start_of_day() -> NaiveTime {
+    NaiveTime::MIN
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DueStatus {
+    Overdue,
+    DueSoon,
+    NotDue,
+}
+
+/// Reads a date written by the user. Besides RFC 3339 dates, like `2024-05-01T18:00:00+02:00`, it accepts
+/// `YYYY-MM-DD`, `YYYY-MM-DD HH:MM`, `today`, `tomorrow` and a number of days or weeks from `today`, like
+/// `+3d` or `+2w`, all in the local time zone. Dates without a time get `default_time`.
+pub fn parse_date(text: &str, default_time: NaiveTime, today: NaiveDate) -> Result<DateTime<FixedOffset>, String> {
+    let text = text.trim();
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(date_time);
+    }
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M") {
+        return to_local(date_time);
+    }
+
+    let date = match text {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        _ => match text.strip_prefix('+') {
+            Some(offset) => parse_offset(offset).and_then(|offset| today.checked_add_signed(offset)),
+            None => NaiveDate::parse_from_str(text, "%Y-%m-%d").ok(),
+        },
+    };
+    match date {
+        Some(date) => to_local(date.and_time(default_time)),
+        None => Err(format!("\"{}\" is not a date, use YYYY-MM-DD, YYYY-MM-DD HH:MM, today, tomorrow or +3d", text)),
+    }
+}
+
+/// A date as written in the local time zone.
+pub fn format_date(date: &DateTime<FixedOffset>) -> String {
+    date.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn parse_offset(offset: &str) -> Option<Duration> {
+    if let Some(days) = offset.strip_suffix('d') {
+        return days.parse().ok().map(Duration::days);
+    }
+    offset.strip_suffix('w')?.parse().ok().map(Duration::weeks)
+}
+
+//Times skipped by a daylight saving change do not exist, repeated ones take the first
+fn to_local(date_time: NaiveDateTime) -> Result<DateTime<FixedOffset>, String> {
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(|date_time| date_time.fixed_offset())
+        .ok_or_else(|| format!("{} does not exist in the local time zone", date_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 2, 28).unwrap()
+    }
+
+    fn local_date(text: &str) -> NaiveDateTime {
+        parse_date(text, end_of_day(), today()).unwrap().with_timezone(&Local).naive_local()
+    }
+
+    #[test]
+    fn parse_dates() {
+        let date = parse_date("2024-05-01T18:00:00+02:00", end_of_day(), today()).unwrap();
+        assert_eq!(date.offset().local_minus_utc(), 2 * 3600);
+        assert_eq!(date, parse_date("2024-05-01T16:00:00Z", end_of_day(), today()).unwrap());
+
+        assert_eq!(local_date("2024-05-01 09:30"), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 30, 0).unwrap());
+        assert_eq!(local_date("2024-05-01"), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_time(end_of_day()));
+        assert_eq!(local_date("today").date(), today());
+        assert_eq!(local_date("tomorrow").date(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert_eq!(local_date("+3d").date(), NaiveDate::from_ymd_opt(2024, 3, 2).unwrap());
+        assert_eq!(local_date("+2w").date(), NaiveDate::from_ymd_opt(2024, 3, 13).unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_dates() {
+        for text in ["", "soon", "2024-13-01", "+d", "+3m", "2024-05-01 25:00"] {
+            assert!(parse_date(text, end_of_day(), today()).is_err(), "{}", text);
+        }
+    }
+}
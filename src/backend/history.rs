@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{task::TaskId, Priority, TagColor, Task};
+
+/// Number of operations that can be undone, older ones are forgotten.
+pub const HISTORY_LENGTH: usize = 100;
+
+/// A task as it was before and after an operation, `None` meaning it did not exist.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskChange {
+    pub task_id: TaskId,
+    pub before: Option<Task>,
+    pub after: Option<Task>,
+}
+
+/// The color of a tag in the registry before and after an operation, `None` meaning it was not registered.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TagChange {
+    pub tag: String,
+    pub before: Option<TagColor>,
+    pub after: Option<TagColor>,
+}
+
+/// The priorities of the board before and after an operation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PriorityChange {
+    pub before: Vec<Priority>,
+    pub after: Vec<Priority>,
+}
+
+/// Every change done to the tasks by one call to the `TaskManager`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Operation {
+    pub description: String,
+    pub changes: Vec<TaskChange>,
+    //Only operations on the tag registry itself, tags registered for new tasks are not undone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_changes: Vec<TagChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_change: Option<PriorityChange>,
+}
+
+impl Operation {
+    pub fn apply(&self, tasks: &mut BTreeMap<TaskId, Task>, tags: &mut BTreeMap<String, TagColor>, priorities: &mut Vec<Priority>) {
+        for change in &self.changes {
+            replace_task(tasks, change.task_id, &change.after);
+        }
+        for change in &self.tag_changes {
+            replace_tag(tags, &change.tag, change.after);
+        }
+        if let Some(priority_change) = &self.priority_change {
+            *priorities = priority_change.after.clone();
+        }
+    }
+
+    pub fn revert(&self, tasks: &mut BTreeMap<TaskId, Task>, tags: &mut BTreeMap<String, TagColor>, priorities: &mut Vec<Priority>) {
+        for change in self.changes.iter().rev() {
+            replace_task(tasks, change.task_id, &change.before);
+        }
+        for change in self.tag_changes.iter().rev() {
+            replace_tag(tags, &change.tag, change.before);
+        }
+        if let Some(priority_change) = &self.priority_change {
+            *priorities = priority_change.before.clone();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.tag_changes.is_empty() && self.priority_change.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    undo_operations: Vec<Operation>,
+    #[serde(default)]
+    redo_operations: Vec<Operation>,
+}
+
+impl History {
+    /// Adds a new operation to undo. Operations that had been undone can not be redone anymore.
+    pub fn record(&mut self, operation: Operation) {
+        self.redo_operations.clear();
+        self.undo_operations.push(operation);
+        if self.undo_operations.len() > HISTORY_LENGTH {
+            self.undo_operations.remove(0);
+        }
+    }
+
+    pub fn next_undo(&self) -> Option<&Operation> {
+        self.undo_operations.last()
+    }
+
+    pub fn next_redo(&self) -> Option<&Operation> {
+        self.redo_operations.last()
+    }
+
+    /// Moves the next operation to undo to the redo stack and returns it.
+    pub fn undo(&mut self) -> Option<&Operation> {
+        let operation = self.undo_operations.pop()?;
+        self.redo_operations.push(operation);
+        self.redo_operations.last()
+    }
+
+    /// Moves the next operation to redo back to the undo stack and returns it.
+    pub fn redo(&mut self) -> Option<&Operation> {
+        let operation = self.redo_operations.pop()?;
+        self.undo_operations.push(operation);
+        self.undo_operations.last()
+    }
+
+    /// Keeps the recorded tasks in sync when a state is renamed.
+    pub fn rename_state(&mut self, state: &str, new_name: &str) {
+        self.undo_operations
+            .iter_mut()
+            .chain(self.redo_operations.iter_mut())
+            .flat_map(|operation| operation.changes.iter_mut())
+            .flat_map(|change| change.before.iter_mut().chain(change.after.iter_mut()))
+            .filter(|task| task.state == state)
+            .for_each(|task| task.state = new_name.to_string());
+    }
+}
+
+/// Lists the tasks that are different in `after`, in id order.
+pub fn diff_tasks(before: &BTreeMap<TaskId, Task>, after: &BTreeMap<TaskId, Task>) -> Vec<TaskChange> {
+    let mut task_ids: Vec<TaskId> = before.keys().chain(after.keys()).cloned().collect();
+    task_ids.sort();
+    task_ids.dedup();
+    task_ids
+        .into_iter()
+        .filter(|task_id| before.get(task_id) != after.get(task_id))
+        .map(|task_id| TaskChange {
+            task_id,
+            before: before.get(&task_id).cloned(),
+            after: after.get(&task_id).cloned(),
+        })
+        .collect()
+}
+
+/// Lists the tags whose color is different in `after`, in alphabetical order.
+pub fn diff_tags(before: &BTreeMap<String, TagColor>, after: &BTreeMap<String, TagColor>) -> Vec<TagChange> {
+    let tags: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    tags.into_iter()
+        .filter(|tag| before.get(*tag) != after.get(*tag))
+        .map(|tag| TagChange {
+            tag: tag.clone(),
+            before: before.get(tag).copied(),
+            after: after.get(tag).copied(),
+        })
+        .collect()
+}
+
+/// The change of the priorities, `None` if they are the same in `after`.
+pub fn diff_priorities(before: &[Priority], after: &[Priority]) -> Option<PriorityChange> {
+    if before == after {
+        return None;
+    }
+    Some(PriorityChange { before: before.to_vec(), after: after.to_vec() })
+}
+
+fn replace_tag(tags: &mut BTreeMap<String, TagColor>, tag: &str, color: Option<TagColor>) {
+    match color {
+        Some(color) => {
+            tags.insert(tag.to_string(), color);
+        }
+        None => {
+            tags.remove(tag);
+        }
+    }
+}
+
+fn replace_task(tasks: &mut BTreeMap<TaskId, Task>, task_id: TaskId, task: &Option<Task>) {
+    match task {
+        Some(task) => {
+            tasks.insert(task_id, task.clone());
+        }
+        None => {
+            tasks.remove(&task_id);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+};
+
+use super::{backups, task::TaskId, DeleteMode, TagColor, TaskFields};
+
+/// Number of events between two snapshots of the whole state in journal mode.
+pub const SNAPSHOT_INTERVAL: u64 = 50;
+
+/// A call to one of the `TaskManager` methods that change the state, replayed to rebuild it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    //`task_id` is the id the new task got, journals written before it was recorded do not have it
+    AddTask {
+        title: String,
+        state: String,
+        description: String,
+        parent: Option<TaskId>,
+        #[serde(default)]
+        task_id: Option<TaskId>,
+    },
+    UpdateTask { task_id: TaskId, title: String, state: String, description: String },
+    SetTaskState { task_id: TaskId, state: String },
+    DeleteTask { task_id: TaskId, delete_mode: DeleteMode },
+    MoveTask { task_id: TaskId, new_parent: Option<TaskId> },
+    CreateTask {
+        fields: TaskFields,
+        parent: Option<TaskId>,
+        #[serde(default)]
+        task_id: Option<TaskId>,
+    },
+    EditTask { task_id: TaskId, fields: TaskFields },
+    Undo,
+    Redo,
+    AddState { state: String },
+    RenameState { state: String, new_name: String },
+    DeleteState { state: String, migration_state: String },
+    MoveState { state: String, new_index: usize },
+    SetTagColor { tag: String, color: TagColor },
+    DeleteTag { tag: String },
+    AddPriority { priority: String, color: TagColor },
+    RenamePriority { priority: String, new_name: String },
+    SetPriorityColor { priority: String, color: TagColor },
+    MovePriority { priority: String, new_index: usize },
+    DeletePriority { priority: String },
+    StartTimer { task_id: TaskId, time: DateTime<Utc> },
+    StopTimer { task_id: TaskId, time: DateTime<Utc> },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::AddTask { title, state, parent, task_id, .. } => describe_creation(f, *task_id, title, state, *parent),
+            Event::UpdateTask { task_id, title, state, .. } => {
+                write!(f, "Edit #{}: title \"{}\", state {}", task_id.0, title, state)
+            }
+            Event::SetTaskState { task_id, state } => write!(f, "Change the state of #{} to {}", task_id.0, state),
+            Event::DeleteTask { task_id, delete_mode: DeleteMode::DeleteChildren } => {
+                write!(f, "Delete #{} with its subtasks", task_id.0)
+            }
+            Event::DeleteTask { task_id, delete_mode: DeleteMode::ReparentChildren } => {
+                write!(f, "Delete #{} moving its subtasks to its parent", task_id.0)
+            }
+            Event::MoveTask { task_id, new_parent: Some(new_parent) } => {
+                write!(f, "Move #{} under #{}", task_id.0, new_parent.0)
+            }
+            Event::MoveTask { task_id, new_parent: None } => write!(f, "Move #{} to the top level", task_id.0),
+            Event::CreateTask { fields, parent, task_id } => {
+                describe_creation(f, *task_id, &fields.title, &fields.state, *parent)
+            }
+            Event::EditTask { task_id, fields } => {
+                write!(f, "Edit #{}: title \"{}\", state {}", task_id.0, fields.title, fields.state)
+            }
+            Event::Undo => write!(f, "Undo"),
+            Event::Redo => write!(f, "Redo"),
+            Event::AddState { state } => write!(f, "Add state {}", state),
+            Event::RenameState { state, new_name } => write!(f, "Rename state {} to {}", state, new_name),
+            Event::DeleteState { state, migration_state } => {
+                write!(f, "Delete state {} moving its tasks to {}", state, migration_state)
+            }
+            Event::MoveState { state, new_index } => write!(f, "Move state {} to position {}", state, new_index + 1),
+            Event::SetTagColor { tag, color } => write!(f, "Color tag {} {}", tag, color),
+            Event::DeleteTag { tag } => write!(f, "Delete tag {}", tag),
+            Event::AddPriority { priority, color } => write!(f, "Add priority {} in {}", priority, color),
+            Event::RenamePriority { priority, new_name } => write!(f, "Rename priority {} to {}", priority, new_name),
+            Event::SetPriorityColor { priority, color } => write!(f, "Color priority {} {}", priority, color),
+            Event::MovePriority { priority, new_index } => {
+                write!(f, "Move priority {} to position {}", priority, new_index + 1)
+            }
+            Event::DeletePriority { priority } => write!(f, "Delete priority {}", priority),
+            Event::StartTimer { task_id, .. } => write!(f, "Start the timer of #{}", task_id.0),
+            Event::StopTimer { task_id, .. } => write!(f, "Stop the timer of #{}", task_id.0),
+        }
+    }
+}
+
+fn describe_creation(f: &mut fmt::Formatter<'_>, task_id: Option<TaskId>, title: &str, state: &str, parent: Option<TaskId>) -> fmt::Result {
+    match task_id {
+        Some(task_id) => write!(f, "Create #{} \"{}\" in {}", task_id.0, title, state)?,
+        None => write!(f, "Create \"{}\" in {}", title, state)?,
+    }
+    match parent {
+        Some(parent) => write!(f, " as a subtask of #{}", parent.0),
+        None => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub sequence: u64,
+    pub time: DateTime<Utc>,
+    pub event: Event,
+}
+
+/// Entries read from a journal with the line they are in, along with the lines that could not be read.
+pub struct Journal {
+    pub path: String,
+    pub entries: Vec<(usize, JournalEntry)>,
+    pub invalid_lines: Vec<(usize, serde_json::Error)>,
+    //The last line has no line break, it is being written or was cut by a crash
+    pub partial_last_line: bool,
+}
+
+pub fn journal_path(file_path: &str) -> String {
+    format!("{}.journal", file_path)
+}
+
+/// Appends an entry as a single line, making sure it is on disk before returning.
+/// A last line cut by a crash is removed first, so the entry starts on its own line.
+pub fn append(journal_path: &str, entry: &JournalEntry) -> io::Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut journal_file = OpenOptions::new().create(true).read(true).append(true).open(journal_path)?;
+    remove_partial_last_line(&mut journal_file)?;
+    journal_file.write_all(line.as_bytes())?;
+    journal_file.sync_data()
+}
+
+fn remove_partial_last_line(journal_file: &mut File) -> io::Result<()> {
+    let length = journal_file.metadata()?.len();
+    if length == 0 {
+        return Ok(());
+    }
+    let mut last_byte = [0];
+    journal_file.seek(SeekFrom::Start(length - 1))?;
+    journal_file.read_exact(&mut last_byte)?;
+    if last_byte[0] == b'\n' {
+        return Ok(());
+    }
+
+    let mut content = Vec::new();
+    journal_file.seek(SeekFrom::Start(0))?;
+    journal_file.read_to_end(&mut content)?;
+    let complete_length = content.iter().rposition(|byte| *byte == b'\n').map_or(0, |position| position + 1);
+    journal_file.set_len(complete_length as u64)
+}
+
+/// Reads every entry of the journal, which is empty if it does not exist yet, without changing it.
+/// A last line without its line break is being written or was cut by a crash, it is left out.
+pub fn read(journal_path: &str) -> io::Result<Journal> {
+    let mut content = match fs::read_to_string(journal_path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error),
+    };
+    let partial_last_line = !content.is_empty() && !content.ends_with('\n');
+    if partial_last_line {
+        content.truncate(content.rfind('\n').map_or(0, |position| position + 1));
+    }
+
+    let mut journal = Journal {
+        path: journal_path.to_string(),
+        entries: Vec::new(),
+        invalid_lines: Vec::new(),
+        partial_last_line,
+    };
+    for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => journal.entries.push((index + 1, entry)),
+            Err(error) => journal.invalid_lines.push((index + 1, error)),
+        }
+    }
+    Ok(journal)
+}
+
+/// Removes the entries up to `sequence`, which have to be part of a snapshot already.
+/// Returns how many entries were removed. Fails without changing it if some lines can not be read.
+pub fn compact(journal_path: &str, sequence: u64) -> io::Result<usize> {
+    let journal = read(journal_path)?;
+    if let Some((line, _)) = journal.invalid_lines.first() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} has {} lines that could not be read, starting at line {}. Fix or remove them before compacting",
+                journal_path,
+                journal.invalid_lines.len(),
+                line
+            ),
+        ));
+    }
+    let (removed_entries, kept_entries): (Vec<JournalEntry>, Vec<JournalEntry>) =
+        journal.entries.into_iter().map(|(_, entry)| entry).partition(|entry| entry.sequence <= sequence);
+    if removed_entries.is_empty() {
+        return Ok(0);
+    }
+
+    let mut content = String::new();
+    for entry in &kept_entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    backups::write_atomically(journal_path, content.as_bytes(), 0)?;
+    Ok(removed_entries.len())
+}
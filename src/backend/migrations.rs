@@ -0,0 +1,328 @@
+use serde_json::{json, Map, Value};
+
+use crate::backend::{priority, TaskError};
+
+/// Version written in the `version` field of the tasks file.
+///
+/// * `0`: the original format, with `tasks`, `valid_states` and `next_valid_id`.
+/// * `1`: adds the `workflow`, still without a `version` field.
+/// * `2`: adds the `version` field.
+/// * `3`: adds the `history` of operations that can be undone.
+/// * `4`: adds the `journal_sequence` of the last journal event included in the file.
+/// * `5`: adds the registry of `tags` and the `tags` of each task.
+/// * `6`: adds the `priorities` of the board and the `priority` of each task.
+/// * `7`: adds the `due_date` and `start_date` of each task.
+/// * `8`: adds the `time_entries` of each task.
+pub const CURRENT_VERSION: u64 = 8;
+
+type Migration = fn(&mut Map<String, Value>);
+
+//Migration from version `i` to `i + 1`
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+];
+
+/// Upgrades a tasks file document of any known version to the current one, one version at a time.
+/// Documents that are not objects are returned as they are so deserializing them reports the error.
+pub fn migrate(mut document: Value) -> Result<Value, TaskError> {
+    let fields = match document.as_object_mut() {
+        Some(fields) => fields,
+        None => return Ok(document),
+    };
+    let version = detect_version(fields)?;
+    if version > CURRENT_VERSION {
+        return Err(TaskError::UnsupportedVersion(version));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(fields);
+    }
+    fields.insert(String::from("version"), json!(CURRENT_VERSION));
+    Ok(document)
+}
+
+fn detect_version(fields: &Map<String, Value>) -> Result<u64, TaskError> {
+    match fields.get("version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| TaskError::InvalidVersion(version.to_string())),
+        None if fields.contains_key("workflow") => Ok(1),
+        None => Ok(0),
+    }
+}
+
+fn migrate_v0_to_v1(fields: &mut Map<String, Value>) {
+    fields.insert(
+        String::from("workflow"),
+        json!({ "transitions": [], "final_states": [] }),
+    );
+}
+
+fn migrate_v1_to_v2(_fields: &mut Map<String, Value>) {
+    //Version 2 only adds the version field, which is written for every version
+}
+
+fn migrate_v2_to_v3(fields: &mut Map<String, Value>) {
+    fields.insert(
+        String::from("history"),
+        json!({ "undo_operations": [], "redo_operations": [] }),
+    );
+}
+
+fn migrate_v3_to_v4(fields: &mut Map<String, Value>) {
+    fields.insert(String::from("journal_sequence"), json!(0));
+}
+
+fn migrate_v4_to_v5(fields: &mut Map<String, Value>) {
+    fields.insert(String::from("tags"), json!({}));
+    if let Some(Value::Object(tasks)) = fields.get_mut("tasks") {
+        for task in tasks.values_mut().filter_map(Value::as_object_mut) {
+            task.insert(String::from("tags"), json!([]));
+        }
+    }
+}
+
+fn migrate_v5_to_v6(fields: &mut Map<String, Value>) {
+    fields.insert(String::from("priorities"), json!(priority::default_priorities()));
+    if let Some(Value::Object(tasks)) = fields.get_mut("tasks") {
+        for task in tasks.values_mut().filter_map(Value::as_object_mut) {
+            task.insert(String::from("priority"), Value::Null);
+        }
+    }
+}
+
+fn migrate_v6_to_v7(fields: &mut Map<String, Value>) {
+    if let Some(Value::Object(tasks)) = fields.get_mut("tasks") {
+        for task in tasks.values_mut().filter_map(Value::as_object_mut) {
+            task.insert(String::from("due_date"), Value::Null);
+            task.insert(String::from("start_date"), Value::Null);
+        }
+    }
+}
+
+fn migrate_v7_to_v8(fields: &mut Map<String, Value>) {
+    if let Some(Value::Object(tasks)) = fields.get_mut("tasks") {
+        for task in tasks.values_mut().filter_map(Value::as_object_mut) {
+            task.insert(String::from("time_entries"), json!([]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current_document() -> Value {
+        json!({
+            "version": 8,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [], "tags": [], "priority": null,
+                      "due_date": null, "start_date": null, "time_entries": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] },
+            "journal_sequence": 0,
+            "tags": {},
+            "priorities": [
+                { "name": "High", "color": "red" },
+                { "name": "Medium", "color": "yellow" },
+                { "name": "Low", "color": "blue" }
+            ]
+        })
+    }
+
+    //Documents as each version wrote them, so they do not change when a new version is added
+    #[test]
+    fn migrate_version_0() {
+        let document = json!({
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1
+        });
+
+        let mut expected_document = current_document();
+        expected_document["workflow"] = json!({ "transitions": [], "final_states": [] });
+        assert_eq!(migrate(document).unwrap(), expected_document);
+    }
+
+    #[test]
+    fn migrate_version_1() {
+        let document = json!({
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            }
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_2() {
+        let document = json!({
+            "version": 2,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            }
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_3() {
+        let document = json!({
+            "version": 3,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] }
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_4() {
+        let document = json!({
+            "version": 4,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] },
+            "journal_sequence": 0
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_5() {
+        let document = json!({
+            "version": 5,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [], "tags": [] }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] },
+            "journal_sequence": 0,
+            "tags": {}
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_6() {
+        let document = json!({
+            "version": 6,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [], "tags": [], "priority": null }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] },
+            "journal_sequence": 0,
+            "tags": {},
+            "priorities": [
+                { "name": "High", "color": "red" },
+                { "name": "Medium", "color": "yellow" },
+                { "name": "Low", "color": "blue" }
+            ]
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_version_7() {
+        let document = json!({
+            "version": 7,
+            "tasks": {
+                "0": { "id": 0, "title": "Title", "state": "Open", "description": "", "parent_task": null, "child_tasks": [], "tags": [], "priority": null,
+                      "due_date": null, "start_date": null }
+            },
+            "valid_states": ["Open", "Done"],
+            "next_valid_id": 1,
+            "workflow": {
+                "transitions": [{ "from": "Open", "to": "Done", "requires_final_children": true }],
+                "final_states": ["Done"]
+            },
+            "history": { "undo_operations": [], "redo_operations": [] },
+            "journal_sequence": 0,
+            "tags": {},
+            "priorities": [
+                { "name": "High", "color": "red" },
+                { "name": "Medium", "color": "yellow" },
+                { "name": "Low", "color": "blue" }
+            ]
+        });
+
+        assert_eq!(migrate(document).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_current_version() {
+        assert_eq!(migrate(current_document()).unwrap(), current_document());
+    }
+
+    #[test]
+    fn migrate_newer_version() {
+        let mut document = current_document();
+        document["version"] = json!(CURRENT_VERSION + 1);
+        assert!(matches!(migrate(document), Err(TaskError::UnsupportedVersion(version)) if version == CURRENT_VERSION + 1));
+    }
+
+    #[test]
+    fn migrate_invalid_version() {
+        let mut document = current_document();
+        document["version"] = json!("two");
+        assert!(matches!(migrate(document), Err(TaskError::InvalidVersion(_))));
+    }
+}
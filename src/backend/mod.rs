@@ -1,7 +1,47 @@
 
 
 pub mod task_manager;
-pub use task_manager::TaskManager;
+pub use task_manager::{DeleteMode, TaskManager};
 
 pub mod task;
-pub use task::Task;
\ No newline at end of file
+pub use task::{Task, TaskFields};
+
+pub mod tag;
+pub use tag::TagColor;
+
+pub mod priority;
+pub use priority::Priority;
+
+pub mod dates;
+pub use dates::DueStatus;
+
+pub mod time_tracking;
+pub use time_tracking::TimeEntry;
+
+pub mod task_view;
+pub use task_view::TaskView;
+
+pub mod task_event;
+pub use task_event::TaskEvent;
+
+pub mod task_error;
+pub use task_error::TaskError;
+
+pub mod workflow;
+pub use workflow::Workflow;
+
+pub(crate) mod recovery;
+
+pub mod backups;
+
+pub(crate) mod migrations;
+
+pub(crate) mod history;
+
+pub mod journal;
+
+pub mod tasks_state;
+pub use tasks_state::TasksState;
+
+pub mod storage;
+pub use storage::Storage;
\ No newline at end of file
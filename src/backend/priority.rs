@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use super::TagColor;
+
+/// A priority tasks can have, shown with its color. The priorities of a board go from the highest to the lowest.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Priority {
+    pub name: String,
+    pub color: TagColor,
+}
+
+impl Priority {
+    pub fn new(name: &str, color: TagColor) -> Priority {
+        Priority { name: name.to_string(), color }
+    }
+}
+
+pub fn default_priorities() -> Vec<Priority> {
+    vec![
+        Priority::new("High", TagColor::Red),
+        Priority::new("Medium", TagColor::Yellow),
+        Priority::new("Low", TagColor::Blue),
+    ]
+}
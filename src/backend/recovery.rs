@@ -0,0 +1,130 @@
+use serde::de::{DeserializeOwned, IgnoredAny};
+use std::{collections::BTreeMap, ops::Range};
+
+use super::{task::TaskId, Task, Workflow};
+
+/// Whatever could be read back from a state file that is no longer valid JSON.
+pub struct SalvagedState {
+    pub tasks: BTreeMap<TaskId, Task>,
+    pub valid_states: Option<Vec<String>>,
+    pub workflow: Option<Workflow>,
+    pub next_valid_id: Option<u64>,
+    pub journal_sequence: Option<u64>,
+}
+
+/// Scans `content` for every object that still parses as a task, no matter how broken the rest of the
+/// document is, and for the top level fields that can still be read. Tasks in the undo history are
+/// skipped, they are deleted tasks or older versions of the current ones.
+pub fn salvage_state(content: &str) -> SalvagedState {
+    let history = find_history(content);
+    let mut tasks = BTreeMap::new();
+    let mut position = 0;
+    while let Some(offset) = content[position..].find('{') {
+        let object_start = position + offset;
+        if let Some(history) = history.as_ref().filter(|history| history.contains(&object_start)) {
+            position = history.end;
+            continue;
+        }
+        let mut stream = serde_json::Deserializer::from_str(&content[object_start..]).into_iter::<Task>();
+        match stream.next() {
+            Some(Ok(task)) => {
+                tasks.entry(task.id).or_insert(task);
+                position = object_start + stream.byte_offset();
+            }
+            _ => position = object_start + 1,
+        }
+    }
+    repair_task_links(&mut tasks);
+
+    SalvagedState {
+        tasks,
+        valid_states: salvage_field(content, "valid_states"),
+        workflow: salvage_field(content, "workflow"),
+        next_valid_id: salvage_field(content, "next_valid_id"),
+        journal_sequence: salvage_field(content, "journal_sequence"),
+    }
+}
+
+fn salvage_field<T: DeserializeOwned>(content: &str, field_name: &str) -> Option<T> {
+    let (_, value_start) = find_key(content, field_name)?;
+    serde_json::Deserializer::from_str(&content[value_start..])
+        .into_iter::<T>()
+        .next()?
+        .ok()
+}
+
+//A broken history is skipped up to the next top level field, found by the keys that never appear in it
+fn find_history(content: &str) -> Option<Range<usize>> {
+    let (key_start, value_start) = find_key(content, "history")?;
+    let mut stream = serde_json::Deserializer::from_str(&content[value_start..]).into_iter::<IgnoredAny>();
+    let history_end = match stream.next() {
+        Some(Ok(_)) => value_start + stream.byte_offset(),
+        _ => ["tasks", "valid_states", "next_valid_id", "workflow", "journal_sequence", "priorities"]
+            .iter()
+            .filter_map(|next_key| find_key(&content[value_start..], next_key))
+            .map(|(next_key_start, _)| value_start + next_key_start)
+            .min()
+            .unwrap_or(content.len()),
+    };
+    Some(key_start..history_end)
+}
+
+//Where a key starts and where its value starts, skipping strings with the same text that are not keys
+fn find_key(content: &str, key: &str) -> Option<(usize, usize)> {
+    let quoted_key = format!("\"{}\"", key);
+    content.match_indices(&quoted_key).find_map(|(key_start, _)| {
+        let key_end = key_start + quoted_key.len();
+        let separator = key_end + content[key_end..].len() - content[key_end..].trim_start().len();
+        content[separator..].starts_with(':').then_some((key_start, separator + 1))
+    })
+}
+
+/// Makes parent and child links agree with each other, dropping the ones pointing to lost tasks.
+pub fn repair_task_links(tasks: &mut BTreeMap<TaskId, Task>) {
+    let task_ids: Vec<TaskId> = tasks.keys().cloned().collect();
+    for task_id in &task_ids {
+        let parent = tasks[task_id].parent_task;
+        if parent.is_some_and(|parent| parent == *task_id || !tasks.contains_key(&parent)) {
+            tasks.get_mut(task_id).unwrap().parent_task = None;
+        }
+    }
+    for task_id in &task_ids {
+        if is_in_parent_cycle(tasks, *task_id) {
+            tasks.get_mut(task_id).unwrap().parent_task = None;
+        }
+    }
+
+    let mut child_tasks: BTreeMap<TaskId, Vec<TaskId>> = BTreeMap::new();
+    for task_id in &task_ids {
+        //Keep the previous order of the children, children missing from the list go last
+        let mut children: Vec<TaskId> = Vec::new();
+        let listed_children = tasks[task_id].child_tasks.iter();
+        let other_children = task_ids.iter().filter(|other| tasks[other].parent_task == Some(*task_id));
+        for child in listed_children.chain(other_children) {
+            let is_child = tasks.get(child).is_some_and(|child| child.parent_task == Some(*task_id));
+            if is_child && !children.contains(child) {
+                children.push(*child);
+            }
+        }
+        child_tasks.insert(*task_id, children);
+    }
+    for (task_id, children) in child_tasks {
+        tasks.get_mut(&task_id).unwrap().child_tasks = children;
+    }
+}
+
+fn is_in_parent_cycle(tasks: &BTreeMap<TaskId, Task>, task_id: TaskId) -> bool {
+    let mut visited_tasks = Vec::new();
+    let mut current = tasks[&task_id].parent_task;
+    while let Some(current_id) = current {
+        if current_id == task_id {
+            return true;
+        }
+        if visited_tasks.contains(&current_id) {
+            return false;
+        }
+        visited_tasks.push(current_id);
+        current = tasks.get(&current_id).and_then(|task| task.parent_task);
+    }
+    false
+}
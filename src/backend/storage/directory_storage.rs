@@ -0,0 +1,150 @@
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use super::{Change, Storage};
+use crate::backend::{backups, recovery, task::TaskId, TaskError, TasksState};
+
+const INDEX_FILE_NAME: &str = "index.json";
+const LOCAL_FILE_NAME: &str = "local.json";
+const TASKS_DIRECTORY_NAME: &str = "tasks";
+
+//Fields that change with every operation, kept out of version control so they never conflict
+const LOCAL_FIELDS: [&str; 2] = ["history", "journal_sequence"];
+
+/// Stores every task as a pretty-printed file in a directory, so that a change to a task only modifies its file.
+///
+/// * `tasks/<id>.json`: one file per task.
+/// * `index.json`: the rest of the state, like `valid_states` and `next_valid_id`.
+/// * `local.json`: the undo history, ignored by git with a `.gitignore` in the directory.
+pub struct DirectoryStorage {
+    directory: PathBuf,
+}
+
+impl DirectoryStorage {
+    pub fn new(directory: &str) -> DirectoryStorage {
+        DirectoryStorage { directory: PathBuf::from(directory) }
+    }
+
+    fn task_path(&self, task_id: TaskId) -> PathBuf {
+        self.directory.join(TASKS_DIRECTORY_NAME).join(format!("{}.json", task_id.0))
+    }
+
+    fn write_task(&self, tasks_state: &TasksState, task_id: TaskId) -> Result<(), TaskError> {
+        let task_path = self.task_path(task_id);
+        match tasks_state.tasks.get(&task_id) {
+            Some(task) => write_if_changed(&task_path, &to_pretty_json(task)?),
+            None => match fs::remove_file(&task_path) {
+                Err(error) if error.kind() != ErrorKind::NotFound => Err(TaskError::Io(error)),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn write_index(&self, tasks_state: &TasksState) -> Result<(), TaskError> {
+        let mut index = tasks_state.to_document()?;
+        let fields = index.as_object_mut().unwrap();
+        fields.remove("tasks");
+        let local_fields: Map<String, Value> = LOCAL_FIELDS
+            .iter()
+            .filter_map(|name| fields.remove_entry(*name))
+            .collect();
+
+        write_if_changed(&self.directory.join(INDEX_FILE_NAME), &to_pretty_json(&index)?)?;
+        write_if_changed(&self.directory.join(LOCAL_FILE_NAME), &to_pretty_json(&local_fields)?)?;
+        write_if_changed(&self.directory.join(".gitignore"), &format!("{}\n", LOCAL_FILE_NAME))
+    }
+}
+
+impl Storage for DirectoryStorage {
+    /// Parent and child links are repaired after loading, as merging task files may leave them inconsistent.
+    fn load(&mut self) -> Result<Option<TasksState>, TaskError> {
+        let mut document = match read_json(&self.directory.join(INDEX_FILE_NAME))? {
+            Some(Value::Object(index)) => index,
+            Some(_) => return Err(TaskError::InvalidFile {
+                path: self.directory.join(INDEX_FILE_NAME).to_string_lossy().to_string(),
+                message: String::from("it is not a JSON object"),
+            }),
+            None => return Ok(None),
+        };
+        if let Some(Value::Object(local_fields)) = read_json(&self.directory.join(LOCAL_FILE_NAME))? {
+            document.extend(local_fields);
+        }
+
+        let mut tasks = Map::new();
+        let tasks_directory = self.directory.join(TASKS_DIRECTORY_NAME);
+        if tasks_directory.exists() {
+            for entry in fs::read_dir(&tasks_directory)? {
+                let task_path = entry?.path();
+                let is_task_file = task_path.extension().is_some_and(|extension| extension == "json")
+                    && task_path.file_stem().is_some_and(|stem| stem.to_string_lossy().parse::<u64>().is_ok());
+                if let (true, Some(task)) = (is_task_file, read_json(&task_path)?) {
+                    tasks.insert(task_path.file_stem().unwrap().to_string_lossy().to_string(), task);
+                }
+            }
+        }
+        document.insert(String::from("tasks"), Value::Object(tasks));
+
+        let mut tasks_state = TasksState::from_document(Value::Object(document))?;
+        recovery::repair_task_links(&mut tasks_state.tasks);
+        let first_free_id = tasks_state.tasks.keys().last().map_or(0, |task_id| task_id.0 + 1);
+        tasks_state.next_valid_id = tasks_state.next_valid_id.max(first_free_id);
+        Ok(Some(tasks_state))
+    }
+
+    fn save(&mut self, tasks_state: &TasksState) -> Result<(), TaskError> {
+        fs::create_dir_all(self.directory.join(TASKS_DIRECTORY_NAME))?;
+        for entry in fs::read_dir(self.directory.join(TASKS_DIRECTORY_NAME))? {
+            let task_path = entry?.path();
+            let task_id = task_path.file_stem().and_then(|stem| stem.to_string_lossy().parse::<u64>().ok());
+            if let Some(task_id) = task_id.filter(|task_id| !tasks_state.tasks.contains_key(&TaskId(*task_id))) {
+                self.write_task(tasks_state, TaskId(task_id))?;
+            }
+        }
+        for task_id in tasks_state.tasks.keys() {
+            self.write_task(tasks_state, *task_id)?;
+        }
+        self.write_index(tasks_state)
+    }
+
+    //Each file is replaced atomically, but not the change as a whole: a crash can leave some of its tasks
+    //written and others not. The index goes last, and the links between parents and children are repaired
+    //when loading, like after a merge.
+    fn write(&mut self, tasks_state: &TasksState, change: &Change) -> Result<(), TaskError> {
+        fs::create_dir_all(self.directory.join(TASKS_DIRECTORY_NAME))?;
+        for task_id in change.changed_tasks {
+            self.write_task(tasks_state, *task_id)?;
+        }
+        self.write_index(tasks_state)
+    }
+}
+
+fn to_pretty_json<T: serde::Serialize>(value: &T) -> Result<String, TaskError> {
+    let mut content = serde_json::to_string_pretty(value)?;
+    content.push('\n');
+    Ok(content)
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>, TaskError> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(TaskError::Io(error)),
+    };
+    serde_json::from_slice(&content).map(Some).map_err(|error| TaskError::InvalidFile {
+        path: path.to_string_lossy().to_string(),
+        message: error.to_string(),
+    })
+}
+
+//Files are only rewritten when needed, so their modification times stay meaningful
+fn write_if_changed(path: &Path, content: &str) -> Result<(), TaskError> {
+    if fs::read(path).is_ok_and(|current_content| current_content == content.as_bytes()) {
+        return Ok(());
+    }
+    backups::write_atomically(&path.to_string_lossy(), content.as_bytes(), 0)?;
+    Ok(())
+}
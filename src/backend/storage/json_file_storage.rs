@@ -0,0 +1,112 @@
+use chrono::Utc;
+use std::{fs, io::ErrorKind, path::Path};
+
+use super::{Change, Storage};
+use crate::backend::{
+    backups,
+    journal::{self, Journal, JournalEntry},
+    TaskError, TasksState,
+};
+
+/// Stores the state as a JSON document, optionally appending the changes to a journal next to it.
+pub struct JsonFileStorage {
+    file_path: String,
+    backup_count: usize,
+    //Backups are only rotated by the first save of a session, so they keep the previous sessions
+    backed_up: bool,
+    journal_enabled: bool,
+}
+
+impl JsonFileStorage {
+    pub fn new(file_path: String) -> JsonFileStorage {
+        JsonFileStorage {
+            file_path,
+            backup_count: backups::DEFAULT_BACKUP_COUNT,
+            backed_up: false,
+            journal_enabled: false,
+        }
+    }
+
+    /// Sets how many previous versions of the file are kept, `0` disables the backups. A new version is kept
+    /// by the first save of each `JsonFileStorage`, later saves only replace the file.
+    pub fn set_backup_count(&mut self, backup_count: usize) {
+        self.backup_count = backup_count;
+    }
+
+    /// With the journal enabled changes are appended to it, and the whole file is only written every few changes.
+    pub fn set_journal_enabled(&mut self, journal_enabled: bool) {
+        self.journal_enabled = journal_enabled;
+    }
+}
+
+impl Storage for JsonFileStorage {
+    /// A file that can not be parsed is copied next to it, so it can be recovered with `TaskManager::recover_from`.
+    fn load(&mut self) -> Result<Option<TasksState>, TaskError> {
+        let content = match fs::read(&self.file_path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(TaskError::Io(error)),
+        };
+
+        let parse_result = serde_json::from_slice(&content)
+            .map_err(TaskError::from)
+            .and_then(TasksState::from_document);
+        match parse_result {
+            Ok(tasks_state) => Ok(Some(tasks_state)),
+            Err(TaskError::Serialization(error)) => {
+                let backup_path = find_free_path(&format!("{}.corrupt", self.file_path));
+                fs::write(&backup_path, &content)?;
+                Err(TaskError::CorruptedFile { path: self.file_path.clone(), backup_path, error })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&mut self, tasks_state: &TasksState) -> Result<(), TaskError> {
+        let serialized_state = serde_json::to_string(tasks_state)?;
+        let backup_count = if self.backed_up { 0 } else { self.backup_count };
+        backups::write_atomically(&self.file_path, serialized_state.as_bytes(), backup_count)?;
+        self.backed_up = true;
+        Ok(())
+    }
+
+    fn keep_next_backup(&mut self) {
+        self.backed_up = false;
+    }
+
+    //Either the whole change is stored or none of it is: the file is replaced at once, and a journal line
+    //that was not fully written is dropped when reading it
+    fn write(&mut self, tasks_state: &TasksState, change: &Change) -> Result<(), TaskError> {
+        if !self.journal_enabled {
+            return self.save(tasks_state);
+        }
+
+        let sequence = tasks_state.journal_sequence();
+        let entry = JournalEntry { sequence, time: Utc::now(), event: change.event.clone() };
+        journal::append(&journal::journal_path(&self.file_path), &entry)?;
+        if sequence.is_multiple_of(journal::SNAPSHOT_INTERVAL) || !Path::new(&self.file_path).exists() {
+            self.save(tasks_state)?;
+        }
+        Ok(())
+    }
+
+    //The journal is read even when it is disabled, so changes are not lost when switching
+    fn load_journal(&mut self) -> Result<Option<Journal>, TaskError> {
+        Ok(Some(journal::read(&journal::journal_path(&self.file_path))?))
+    }
+
+    fn compact_journal(&mut self, tasks_state: &TasksState) -> Result<usize, TaskError> {
+        self.save(tasks_state)?;
+        Ok(journal::compact(&journal::journal_path(&self.file_path), tasks_state.journal_sequence())?)
+    }
+}
+
+fn find_free_path(path: &str) -> String {
+    let mut free_path = path.to_string();
+    let mut suffix = 1;
+    while Path::new(&free_path).exists() {
+        free_path = format!("{}.{}", path, suffix);
+        suffix += 1;
+    }
+    free_path
+}
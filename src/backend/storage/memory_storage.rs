@@ -0,0 +1,25 @@
+use super::Storage;
+use crate::backend::{TaskError, TasksState};
+
+/// Keeps the state only while the application runs, useful for tests.
+#[derive(Default)]
+pub struct MemoryStorage {
+    tasks_state: Option<TasksState>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&mut self) -> Result<Option<TasksState>, TaskError> {
+        Ok(self.tasks_state.clone())
+    }
+
+    fn save(&mut self, tasks_state: &TasksState) -> Result<(), TaskError> {
+        self.tasks_state = Some(tasks_state.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+pub mod directory_storage;
+pub use directory_storage::DirectoryStorage;
+pub mod json_file_storage;
+pub use json_file_storage::JsonFileStorage;
+pub mod memory_storage;
+pub use memory_storage::MemoryStorage;
+pub mod sqlite_storage;
+pub use sqlite_storage::SqliteStorage;
+
+use super::{
+    journal::{Event, Journal},
+    task::TaskId,
+    TaskError, TasksState,
+};
+
+/// A change that was just applied to the `TasksState` stored with it.
+pub struct Change<'a> {
+    pub event: &'a Event,
+    //Tasks added, modified or removed by the change
+    pub changed_tasks: &'a [TaskId],
+}
+
+/// Where a `TaskManager` keeps its state between runs. It is `Send` so the `TaskManager` can be shared
+/// between threads.
+pub trait Storage: Send {
+    /// Reads the stored state, `None` when nothing has been stored yet.
+    fn load(&mut self) -> Result<Option<TasksState>, TaskError>;
+
+    /// Replaces everything that is stored with `tasks_state`.
+    fn save(&mut self, tasks_state: &TasksState) -> Result<(), TaskError>;
+
+    /// Stores a change already applied to `tasks_state`. Each storage documents what is left after a crash
+    /// in the middle of it.
+    fn write(&mut self, tasks_state: &TasksState, _change: &Change) -> Result<(), TaskError> {
+        self.save(tasks_state)
+    }
+
+    /// Makes the next save keep a backup of what is stored, even if one was already kept in this session.
+    fn keep_next_backup(&mut self) {}
+
+    /// Journal of the changes, whose entries after the loaded `TasksState::journal_sequence` have to be replayed.
+    fn load_journal(&mut self) -> Result<Option<Journal>, TaskError> {
+        Ok(None)
+    }
+
+    /// Stores the whole state so the journal entries included in it can be removed. Returns how many were removed.
+    fn compact_journal(&mut self, tasks_state: &TasksState) -> Result<usize, TaskError> {
+        self.save(tasks_state)?;
+        Ok(0)
+    }
+}
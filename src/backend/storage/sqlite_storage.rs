@@ -0,0 +1,95 @@
+use rusqlite::{params, Connection, Transaction};
+use serde_json::{Map, Value};
+
+use super::{Change, Storage};
+use crate::backend::{TaskError, TasksState};
+
+/// Stores the state in an SQLite database with a row per task, so a change only writes the tasks it modified.
+/// The rest of the state is kept as one JSON value per field.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(file_path: &str) -> Result<SqliteStorage, TaskError> {
+        SqliteStorage::create(Connection::open(file_path)?)
+    }
+
+    fn create(connection: Connection) -> Result<SqliteStorage, TaskError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tasks (id INTEGER PRIMARY KEY, data TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS properties (name TEXT PRIMARY KEY, value TEXT NOT NULL);",
+        )?;
+        Ok(SqliteStorage { connection })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<Option<TasksState>, TaskError> {
+        let mut document = Map::new();
+        let mut statement = self.connection.prepare("SELECT name, value FROM properties")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let value: String = row.get(1)?;
+            document.insert(row.get(0)?, serde_json::from_str(&value)?);
+        }
+        if document.is_empty() {
+            return Ok(None);
+        }
+
+        let mut tasks = Map::new();
+        let mut statement = self.connection.prepare("SELECT id, data FROM tasks")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let data: String = row.get(1)?;
+            tasks.insert(id.to_string(), serde_json::from_str(&data)?);
+        }
+        document.insert(String::from("tasks"), Value::Object(tasks));
+        Ok(Some(TasksState::from_document(Value::Object(document))?))
+    }
+
+    fn save(&mut self, tasks_state: &TasksState) -> Result<(), TaskError> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM tasks", [])?;
+        for (task_id, task) in &tasks_state.tasks {
+            transaction.execute(
+                "INSERT INTO tasks (id, data) VALUES (?1, ?2)",
+                params![task_id.0 as i64, serde_json::to_string(task)?],
+            )?;
+        }
+        write_properties(&transaction, tasks_state)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    //A single transaction, so either the whole change is stored or none of it is
+    fn write(&mut self, tasks_state: &TasksState, change: &Change) -> Result<(), TaskError> {
+        let transaction = self.connection.transaction()?;
+        for task_id in change.changed_tasks {
+            match tasks_state.tasks.get(task_id) {
+                Some(task) => transaction.execute(
+                    "INSERT OR REPLACE INTO tasks (id, data) VALUES (?1, ?2)",
+                    params![task_id.0 as i64, serde_json::to_string(task)?],
+                )?,
+                None => transaction.execute("DELETE FROM tasks WHERE id = ?1", params![task_id.0 as i64])?,
+            };
+        }
+        write_properties(&transaction, tasks_state)?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+//Writes every field of the state but the tasks
+fn write_properties(transaction: &Transaction, tasks_state: &TasksState) -> Result<(), TaskError> {
+    let document = tasks_state.to_document()?;
+    let fields = document.as_object().into_iter().flatten().filter(|(name, _)| *name != "tasks");
+    for (name, value) in fields {
+        transaction.execute(
+            "INSERT OR REPLACE INTO properties (name, value) VALUES (?1, ?2)",
+            params![name, value.to_string()],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Color a tag is shown with. New tags get the next color of `TagColor::ALL`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+}
+
+impl TagColor {
+    pub const ALL: [TagColor; 7] = [
+        TagColor::Red,
+        TagColor::Green,
+        TagColor::Yellow,
+        TagColor::Blue,
+        TagColor::Magenta,
+        TagColor::Cyan,
+        TagColor::Gray,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TagColor::Red => "red",
+            TagColor::Green => "green",
+            TagColor::Yellow => "yellow",
+            TagColor::Blue => "blue",
+            TagColor::Magenta => "magenta",
+            TagColor::Cyan => "cyan",
+            TagColor::Gray => "gray",
+        }
+    }
+}
+
+impl fmt::Display for TagColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TagColor {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<TagColor, String> {
+        TagColor::ALL
+            .into_iter()
+            .find(|color| color.name() == name)
+            .ok_or_else(|| {
+                let color_names: Vec<&str> = TagColor::ALL.iter().map(TagColor::name).collect();
+                format!("\"{}\" is not a color, use one of {}", name, color_names.join(", "))
+            })
+    }
+}
+
+/// Tags are single words, so they can be written separated by spaces or commas.
+pub fn is_valid_tag_name(tag: &str) -> bool {
+    !tag.is_empty() && !tag.contains(|character: char| character.is_whitespace() || character == ',')
+}
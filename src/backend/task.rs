@@ -1,9 +1,13 @@
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+use super::time_tracking::TimeEntry;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TaskId(pub u64);
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Task {
     pub id: TaskId,
     pub title: String,
@@ -11,4 +15,63 @@ pub struct Task {
     pub description: String,
     pub parent_task: Option<TaskId>,
     pub child_tasks: Vec<TaskId>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    //One of the priorities of the board, or none
+    #[serde(default)]
+    pub priority: Option<String>,
+    //Dates keep the time zone they were given in
+    #[serde(default)]
+    pub due_date: Option<DateTime<FixedOffset>>,
+    //The task is deferred until then
+    #[serde(default)]
+    pub start_date: Option<DateTime<FixedOffset>>,
+    //In the order they were started, only the last one can be running
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+}
+
+/// The fields of a task that are edited together, like in the task form.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskFields {
+    pub title: String,
+    pub state: String,
+    pub description: String,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub due_date: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    pub start_date: Option<DateTime<FixedOffset>>,
+}
+
+impl Task {
+    pub fn fields(&self) -> TaskFields {
+        TaskFields {
+            title: self.title.clone(),
+            state: self.state.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            priority: self.priority.clone(),
+            due_date: self.due_date,
+            start_date: self.start_date,
+        }
+    }
+
+    /// Whether the task has a start date that has not come yet.
+    pub fn is_deferred(&self, now: DateTime<Utc>) -> bool {
+        self.start_date.is_some_and(|start_date| start_date > now)
+    }
+
+    /// The entry of the timer of the task, if it is running.
+    pub fn running_entry(&self) -> Option<&TimeEntry> {
+        self.time_entries.last().filter(|entry| entry.is_running())
+    }
+
+    /// Time spent on the task itself, counting the running timer up to `now`.
+    pub fn time_spent(&self, now: DateTime<Utc>) -> Duration {
+        self.time_entries.iter().map(|entry| entry.duration(now)).fold(Duration::zero(), |total, duration| total + duration)
+    }
 }
@@ -0,0 +1,120 @@
+use std::{error::Error, fmt, io};
+
+use super::task::TaskId;
+
+#[derive(Debug)]
+pub enum TaskError {
+    TaskNotFound(TaskId),
+    TaskAlreadyExists(TaskId),
+    ParentNotFound(TaskId),
+    CyclicParent { task: TaskId, parent: TaskId },
+    InvalidState(String),
+    EmptyStateName,
+    StateAlreadyExists(String),
+    InvalidStatePosition(usize),
+    InvalidTagName(String),
+    TagNotFound(String),
+    InvalidPriority(String),
+    EmptyPriorityName,
+    PriorityAlreadyExists(String),
+    InvalidPriorityPosition(usize),
+    StartAfterDue,
+    TimerAlreadyRunning(TaskId),
+    TimerNotRunning(TaskId),
+    TransitionNotAllowed { from: String, to: String },
+    UnfinishedSubtasks { from: String, to: String },
+    UnsupportedVersion(u64),
+    InvalidVersion(String),
+    CorruptedJournal { path: String, line: usize, message: String },
+    InvalidFile { path: String, message: String },
+    CorruptedFile { path: String, backup_path: String, error: serde_json::Error },
+    Io(io::Error),
+    Serialization(serde_json::Error),
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::TaskNotFound(task_id) => write!(f, "Task {} does not exist", task_id.0),
+            TaskError::TaskAlreadyExists(task_id) => write!(f, "Task {} already exists", task_id.0),
+            TaskError::ParentNotFound(task_id) => write!(f, "Parent task {} does not exist", task_id.0),
+            TaskError::CyclicParent { task, parent } => write!(
+                f,
+                "Task {} can not be moved under task {}, it is the task itself or one of its subtasks",
+                task.0, parent.0
+            ),
+            TaskError::InvalidState(state) => write!(f, "\"{}\" is not a valid state", state),
+            TaskError::EmptyStateName => write!(f, "States need a name"),
+            TaskError::StateAlreadyExists(state) => write!(f, "State \"{}\" already exists", state),
+            TaskError::InvalidStatePosition(position) => write!(f, "There is no state position {}", position),
+            TaskError::InvalidTagName(tag) => {
+                write!(f, "\"{}\" is not a valid tag, tags can not be empty or contain spaces or commas", tag)
+            }
+            TaskError::TagNotFound(tag) => write!(f, "Tag \"{}\" does not exist", tag),
+            TaskError::InvalidPriority(priority) => write!(f, "\"{}\" is not a valid priority", priority),
+            TaskError::EmptyPriorityName => write!(f, "Priorities need a name"),
+            TaskError::PriorityAlreadyExists(priority) => write!(f, "Priority \"{}\" already exists", priority),
+            TaskError::InvalidPriorityPosition(position) => write!(f, "There is no priority position {}", position),
+            TaskError::StartAfterDue => write!(f, "The start date has to be before the due date"),
+            TaskError::TimerAlreadyRunning(task_id) => write!(f, "The timer of task {} is already running", task_id.0),
+            TaskError::TimerNotRunning(task_id) => write!(f, "The timer of task {} is not running", task_id.0),
+            TaskError::TransitionNotAllowed { from, to } => {
+                write!(f, "The workflow does not allow moving from \"{}\" to \"{}\"", from, to)
+            }
+            TaskError::UnfinishedSubtasks { from, to } => write!(
+                f,
+                "Moving from \"{}\" to \"{}\" requires every subtask to be in a final state",
+                from, to
+            ),
+            TaskError::UnsupportedVersion(version) => write!(
+                f,
+                "The tasks file was written by a newer version of TAnt (format version {})",
+                version
+            ),
+            TaskError::InvalidVersion(version) => write!(f, "The tasks file has an invalid format version: {}", version),
+            TaskError::CorruptedJournal { path, line, message } => {
+                write!(f, "Line {} of {} could not be replayed: {}", line, path, message)
+            }
+            TaskError::InvalidFile { path, message } => write!(f, "{} could not be read: {}", path, message),
+            TaskError::CorruptedFile { path, backup_path, error } => write!(
+                f,
+                "{} could not be read ({}), a copy was saved to {}",
+                path, error, backup_path
+            ),
+            TaskError::Io(error) => write!(f, "Could not access the tasks file: {}", error),
+            TaskError::Serialization(error) => write!(f, "Could not serialize the tasks: {}", error),
+            TaskError::Database(error) => write!(f, "Could not access the tasks database: {}", error),
+        }
+    }
+}
+
+impl Error for TaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TaskError::CorruptedFile { error, .. } => Some(error),
+            TaskError::Io(error) => Some(error),
+            TaskError::Serialization(error) => Some(error),
+            TaskError::Database(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TaskError {
+    fn from(error: io::Error) -> TaskError {
+        TaskError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for TaskError {
+    fn from(error: serde_json::Error) -> TaskError {
+        TaskError::Serialization(error)
+    }
+}
+
+impl From<rusqlite::Error> for TaskError {
+    fn from(error: rusqlite::Error) -> TaskError {
+        TaskError::Database(error)
+    }
+}
@@ -0,0 +1,65 @@
+use std::{collections::BTreeMap, iter};
+
+use super::{history::TaskChange, task::TaskId, Task, TaskView};
+
+/// A change to a task that other programs may react to, like the hooks of a workspace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskEvent {
+    Created(TaskView),
+    StateChanged { task: TaskView, previous_state: String },
+    //The task as it was before being deleted
+    Deleted(TaskView),
+}
+
+impl TaskEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskEvent::Created(_) => "created",
+            TaskEvent::StateChanged { .. } => "state_changed",
+            TaskEvent::Deleted(_) => "deleted",
+        }
+    }
+
+    pub fn task(&self) -> &TaskView {
+        match self {
+            TaskEvent::Created(task) | TaskEvent::StateChanged { task, .. } | TaskEvent::Deleted(task) => task,
+        }
+    }
+}
+
+/// The events of changes already applied to `tasks`, or already reverted when `reverted` is set.
+pub(crate) fn task_events(changes: &[TaskChange], reverted: bool, tasks: &BTreeMap<TaskId, Task>) -> Vec<TaskEvent> {
+    let changes: Vec<(Option<&Task>, Option<&Task>)> = changes
+        .iter()
+        .map(|change| match reverted {
+            false => (change.before.as_ref(), change.after.as_ref()),
+            true => (change.after.as_ref(), change.before.as_ref()),
+        })
+        .collect();
+    //Deleted tasks are still needed to know the depth of their deleted subtasks
+    let deleted_tasks: BTreeMap<TaskId, &Task> = changes
+        .iter()
+        .filter_map(|change| match change {
+            (Some(before), None) => Some((before.id, *before)),
+            _ => None,
+        })
+        .collect();
+    let find_task = |task_id: &TaskId| tasks.get(task_id).or_else(|| deleted_tasks.get(task_id).copied());
+    let task_view = |task: &Task| {
+        let ancestors = iter::successors(task.parent_task, |parent| find_task(parent)?.parent_task);
+        TaskView::new(task, ancestors.count())
+    };
+
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            (None, Some(after)) => Some(TaskEvent::Created(task_view(after))),
+            (Some(before), None) => Some(TaskEvent::Deleted(task_view(before))),
+            (Some(before), Some(after)) if before.state != after.state => Some(TaskEvent::StateChanged {
+                task: task_view(after),
+                previous_state: before.state.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
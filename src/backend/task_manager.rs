@@ -1,116 +1,445 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::Write, collections::{BTreeMap}};
+use std::{collections::{BTreeMap, BTreeSet}, fs, io::{self, ErrorKind}};
 
-use crate::backend::Task;
+use crate::backend::{
+    dates::{self, DueStatus}, history::{self, History, Operation}, journal::Event, recovery, storage::{Change, Storage}, tag,
+    task_event, time_tracking, Priority, TagColor, Task, TaskError, TaskEvent, TaskFields, TaskView, TasksState, TimeEntry, Workflow,
+};
 
 use super::task::TaskId;
 
-#[derive(Serialize, Deserialize)]
-struct TasksState {
-    tasks: BTreeMap<TaskId, Task>,
-    valid_states: Vec<String>,
-    next_valid_id: u64,
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeleteMode {
+    DeleteChildren,
+    ReparentChildren,
 }
 
+/// Called after every change to the tasks that is stored.
+pub type EventListener = Box<dyn FnMut(&TaskEvent) + Send>;
+
 pub struct TaskManager {
     tasks_state: TasksState,
-    file_path_opt: Option<String>,
+    storage: Box<dyn Storage>,
+    replaying: bool,
+    event_listener: Option<EventListener>,
 }
 
 impl TaskManager {
-    pub fn new(file_path_opt: Option<String>) -> TaskManager {
+    pub fn new(storage: Box<dyn Storage>) -> TaskManager {
         TaskManager {
-            tasks_state: TasksState {
-                tasks: BTreeMap::new(),
-                valid_states: Vec::new(),
-                next_valid_id: 0,
-            },
-            file_path_opt
-        }
-    }
-
-    pub fn initialize(&mut self) {
-        if let Some(ref file_path) = self.file_path_opt {
-            let open_file_result = File::open(file_path);
-            if let Ok(file) = open_file_result {
-                let tasks_state_from_json = serde_json::from_reader(file);
-                if let Ok(tasks_state) = tasks_state_from_json {
-                    self.tasks_state = tasks_state;
-                    return;
-                }
+            tasks_state: TasksState::default(),
+            storage,
+            replaying: false,
+            event_listener: None,
+        }
+    }
+
+    /// Sets the function told about tasks being created, changing state or deleted. Changes replayed from the
+    /// journal while loading are not told, they already happened.
+    pub fn set_event_listener(&mut self, event_listener: EventListener) {
+        self.event_listener = Some(event_listener);
+    }
+
+    /// Loads the stored tasks. When they can not be read the tasks start from the default states.
+    /// Changes in the journal of the storage that are not part of the stored state are replayed on top of it.
+    pub fn initialize(&mut self) -> Result<(), TaskError> {
+        match self.storage.load() {
+            Ok(Some(tasks_state)) => self.tasks_state = tasks_state,
+            load_result => {
+                //We could not initialize, we are going to add some default states
+                self.set_default_states();
+                load_result?;
             }
         }
-        //We could not initialize, we are going to add some default states
-        self.add_state("Open".to_string());
-        self.add_state("Selected for development".to_string());
-        self.add_state("In progress".to_string());
-        self.add_state("Done".to_string());
+        self.replay_journal()
     }
 
-    pub fn save(&self) {
-        if let Some(ref file_path) = self.file_path_opt {
-            let create_file_result = File::create(file_path);
-            if let Ok(mut created_file) = create_file_result {
-                let serialized_state_result = serde_json::to_string(&self.tasks_state);
-                if let Ok(serialized_state) = serialized_state_result {
-                    let write_result = created_file.write(serialized_state.as_bytes());
-                    if let Err(error) = write_result {
-                        panic!("{}", error);
-                    }
-                }
+    /// Replaces the current tasks with every task that can still be read from a broken tasks file.
+    /// Returns how many tasks were recovered.
+    pub fn recover_from(&mut self, corrupted_file_path: &str) -> Result<usize, TaskError> {
+        let content = fs::read(corrupted_file_path)?;
+        let salvaged_state = recovery::salvage_state(&String::from_utf8_lossy(&content));
+
+        self.set_default_states();
+        if let Some(valid_states) = salvaged_state.valid_states {
+            self.tasks_state.valid_states = valid_states;
+        }
+        if let Some(workflow) = salvaged_state.workflow {
+            self.tasks_state.workflow = workflow;
+        }
+        //Tasks in states that were lost keep their state
+        for task in salvaged_state.tasks.values() {
+            if !self.tasks_state.valid_states.contains(&task.state) {
+                self.tasks_state.valid_states.push(task.state.clone());
             }
-        } 
+        }
+        let first_free_id = salvaged_state.tasks.keys().last().map_or(0, |task_id| task_id.0 + 1);
+        self.tasks_state.next_valid_id = salvaged_state.next_valid_id.unwrap_or(0).max(first_free_id);
+        let recovered_tasks = salvaged_state.tasks.len();
+        self.tasks_state.tasks = salvaged_state.tasks;
+        self.tasks_state.history = History::default();
+        self.tasks_state.journal_sequence = salvaged_state.journal_sequence.unwrap_or(0);
+        self.register_task_tags();
+        let replay_result = self.replay_journal();
+        self.save()?;
+        replay_result?;
+        Ok(recovered_tasks)
     }
 
-    pub fn add_task(&mut self, title: String, state: String, description: String) -> Option<TaskId> {
-        let new_task = Task {
-            id: TaskId(self.tasks_state.next_valid_id),
-            title,
-            state,
-            description,
-            parent_task: None,
-            child_tasks: Vec::new(),
+    /// Replaces the tasks with the ones stored in another storage, such as a backup.
+    pub fn restore_from(&mut self, storage: &mut dyn Storage) -> Result<(), TaskError> {
+        let restored_state = storage.load()?.ok_or_else(|| {
+            TaskError::Io(io::Error::new(ErrorKind::NotFound, "There are no tasks to restore"))
+        })?;
+        //The journal entries are not part of the restored state, they must not be replayed on top of it
+        let last_journal_sequence = self.storage
+            .load_journal()?
+            .and_then(|journal| journal.entries.last().map(|(_, entry)| entry.sequence))
+            .unwrap_or(0);
+        let journal_sequence = self.tasks_state.journal_sequence.max(last_journal_sequence);
+        self.tasks_state = restored_state;
+        self.tasks_state.journal_sequence = journal_sequence;
+        //The replaced tasks can be restored back
+        self.storage.keep_next_backup();
+        self.save()
+    }
+
+    /// Stores the whole state and removes the journal entries included in it. Returns how many were removed.
+    pub fn compact_journal(&mut self) -> Result<usize, TaskError> {
+        self.storage.compact_journal(&self.tasks_state)
+    }
+
+    fn replay_journal(&mut self) -> Result<(), TaskError> {
+        let journal = match self.storage.load_journal()? {
+            Some(journal) => journal,
+            None => return Ok(()),
         };
 
-        let task_id = new_task.id;
-        self.tasks_state.next_valid_id += 1;
+        let journal_path = journal.path;
+        let mut first_error = journal.invalid_lines.into_iter().next().map(|(line, error)| {
+            TaskError::CorruptedJournal { path: journal_path.clone(), line, message: error.to_string() }
+        });
+        self.replaying = true;
+        for (line, entry) in journal.entries {
+            if entry.sequence <= self.tasks_state.journal_sequence {
+                continue;
+            }
+            self.tasks_state.journal_sequence = entry.sequence;
+            if let Err(error) = self.apply_event(entry.event) {
+                first_error.get_or_insert(TaskError::CorruptedJournal {
+                    path: journal_path.clone(),
+                    line,
+                    message: error.to_string(),
+                });
+            }
+        }
+        self.replaying = false;
+        if first_error.is_some() {
+            //Keep what could be replayed so the broken entries are not replayed again
+            self.save()?;
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn apply_event(&mut self, event: Event) -> Result<(), TaskError> {
+        match event {
+            Event::AddTask { title, state, description, parent, task_id } => {
+                self.add_task_with_id(title, state, description, parent, task_id).map(|_| ())
+            }
+            Event::UpdateTask { task_id, title, state, description } => self.update_task(task_id, title, state, description),
+            Event::SetTaskState { task_id, state } => self.set_task_state(task_id, state),
+            Event::DeleteTask { task_id, delete_mode } => self.delete_task(task_id, delete_mode),
+            Event::MoveTask { task_id, new_parent } => self.move_task(task_id, new_parent),
+            Event::CreateTask { fields, parent, task_id } => self.create_task_with_id(fields, parent, task_id).map(|_| ()),
+            Event::EditTask { task_id, fields } => self.edit_task(task_id, fields),
+            Event::Undo => self.undo().map(|_| ()),
+            Event::Redo => self.redo().map(|_| ()),
+            Event::AddState { state } => self.add_state(state),
+            Event::RenameState { state, new_name } => self.rename_state(&state, new_name),
+            Event::DeleteState { state, migration_state } => self.delete_state(&state, &migration_state),
+            Event::MoveState { state, new_index } => self.move_state(&state, new_index),
+            Event::SetTagColor { tag, color } => self.set_tag_color(&tag, color),
+            Event::DeleteTag { tag } => self.delete_tag(&tag),
+            Event::AddPriority { priority, color } => self.add_priority(priority, color),
+            Event::RenamePriority { priority, new_name } => self.rename_priority(&priority, new_name),
+            Event::SetPriorityColor { priority, color } => self.set_priority_color(&priority, color),
+            Event::MovePriority { priority, new_index } => self.move_priority(&priority, new_index),
+            Event::DeletePriority { priority } => self.delete_priority(&priority),
+            Event::StartTimer { task_id, time } => self.start_timer(task_id, time),
+            Event::StopTimer { task_id, time } => self.stop_timer(task_id, time),
+        }
+    }
+
+    //Stores a change that was already applied to the state
+    fn persist(&mut self, event: Event, changed_tasks: Vec<TaskId>) -> Result<(), TaskError> {
+        if self.replaying {
+            return Ok(());
+        }
+        self.tasks_state.journal_sequence += 1;
+        let change = Change { event: &event, changed_tasks: &changed_tasks };
+        self.storage.write(&self.tasks_state, &change)
+    }
 
-        if !self.is_task_valid(&new_task) {
-            return None;
+    fn notify(&mut self, events: Vec<TaskEvent>) {
+        if let (false, Some(event_listener)) = (self.replaying, &mut self.event_listener) {
+            events.iter().for_each(event_listener);
         }
-        
-        self.tasks_state.tasks.insert(task_id, new_task);
-        self.save();
-        Some(task_id)
     }
 
-    pub fn add_task_with_parent(&mut self, title: String, state: String, description: String, parent: &TaskId) -> Option<TaskId> {
+    fn set_default_states(&mut self) {
+        self.tasks_state.valid_states = vec![
+            "Open".to_string(),
+            "Selected for development".to_string(),
+            "In progress".to_string(),
+            "Done".to_string(),
+        ];
+        self.tasks_state.workflow = Workflow {
+            transitions: Vec::new(),
+            final_states: vec!["Done".to_string()],
+        };
+    }
+
+    pub fn save(&mut self) -> Result<(), TaskError> {
+        self.storage.save(&self.tasks_state)
+    }
+
+    pub fn add_task(&mut self, title: String, state: String, description: String) -> Result<TaskId, TaskError> {
+        self.add_task_with_id(title, state, description, None, None)
+    }
+
+    pub fn add_task_with_parent(&mut self, title: String, state: String, description: String, parent: &TaskId) -> Result<TaskId, TaskError> {
+        self.add_task_with_id(title, state, description, Some(*parent), None)
+    }
+
+    //Replayed tasks get the id they were given, so the changes after a skipped one still find their task
+    fn add_task_with_id(
+        &mut self,
+        title: String,
+        state: String,
+        description: String,
+        parent: Option<TaskId>,
+        task_id: Option<TaskId>,
+    ) -> Result<TaskId, TaskError> {
+        let task_id = task_id.unwrap_or(TaskId(self.tasks_state.next_valid_id));
+        let operation_description = format!("Create \"{}\"", title);
+        let event = Event::AddTask {
+            title: title.clone(),
+            state: state.clone(),
+            description: description.clone(),
+            parent,
+            task_id: Some(task_id),
+        };
+        self.record(operation_description, event, |task_manager| task_manager.insert_task(task_id, title, state, description, parent))
+    }
+
+    pub fn update_task(&mut self, task_id: TaskId, title: String, state: String, description: String) -> Result<(), TaskError> {
+        let operation_description = format!("Edit \"{}\"", self.get_task_title(task_id));
+        let event = Event::UpdateTask { task_id, title: title.clone(), state: state.clone(), description: description.clone() };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.check_state_change(task_id, &state)?;
+
+            let task = task_manager.tasks_state.tasks.get_mut(&task_id).unwrap();
+            task.title = title;
+            task.state = state;
+            task.description = description;
+            Ok(())
+        })
+    }
+
+    /// Adds a task with every field of `fields`, under `parent` when there is one. Its tags are added to the
+    /// tag registry if they are new.
+    pub fn create_task(&mut self, fields: TaskFields, parent: Option<TaskId>) -> Result<TaskId, TaskError> {
+        self.create_task_with_id(fields, parent, None)
+    }
+
+    fn create_task_with_id(&mut self, fields: TaskFields, parent: Option<TaskId>, task_id: Option<TaskId>) -> Result<TaskId, TaskError> {
+        let task_id = task_id.unwrap_or(TaskId(self.tasks_state.next_valid_id));
+        let operation_description = format!("Create \"{}\"", fields.title);
+        let event = Event::CreateTask { fields: fields.clone(), parent, task_id: Some(task_id) };
+        self.record(operation_description, event, |task_manager| {
+            check_tag_names(&fields.tags)?;
+            task_manager.check_priority(&fields.priority)?;
+            check_dates(&fields)?;
+
+            task_manager.insert_task(task_id, fields.title, fields.state, fields.description, parent)?;
+            let task = task_manager.tasks_state.tasks.get_mut(&task_id).unwrap();
+            task.tags = fields.tags;
+            task.priority = fields.priority;
+            task.due_date = fields.due_date;
+            task.start_date = fields.start_date;
+            Ok(task_id)
+        })
+    }
+
+    /// Replaces every field of `fields` of a task, like `update_task` does with the title, state and description.
+    pub fn edit_task(&mut self, task_id: TaskId, fields: TaskFields) -> Result<(), TaskError> {
+        let operation_description = format!("Edit \"{}\"", self.get_task_title(task_id));
+        let event = Event::EditTask { task_id, fields: fields.clone() };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.check_state_change(task_id, &fields.state)?;
+            check_tag_names(&fields.tags)?;
+            task_manager.check_priority(&fields.priority)?;
+            check_dates(&fields)?;
+
+            let task = task_manager.tasks_state.tasks.get_mut(&task_id).unwrap();
+            task.title = fields.title;
+            task.state = fields.state;
+            task.description = fields.description;
+            task.tags = fields.tags;
+            task.priority = fields.priority;
+            task.due_date = fields.due_date;
+            task.start_date = fields.start_date;
+            Ok(())
+        })
+    }
+
+    pub fn set_task_state(&mut self, task_id: TaskId, state: String) -> Result<(), TaskError> {
+        let operation_description = format!("Change the state of \"{}\" to {}", self.get_task_title(task_id), state);
+        let event = Event::SetTaskState { task_id, state: state.clone() };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.check_state_change(task_id, &state)?;
+
+            task_manager.tasks_state.tasks.get_mut(&task_id).unwrap().state = state;
+            Ok(())
+        })
+    }
+
+    pub fn delete_task(&mut self, task_id: TaskId, delete_mode: DeleteMode) -> Result<(), TaskError> {
+        let operation_description = format!("Delete \"{}\"", self.get_task_title(task_id));
+        let event = Event::DeleteTask { task_id, delete_mode };
+        self.record(operation_description, event, |task_manager| task_manager.remove_task(task_id, delete_mode))
+    }
+
+    pub fn move_task(&mut self, task_id: TaskId, new_parent: Option<TaskId>) -> Result<(), TaskError> {
+        let operation_description = format!("Move \"{}\"", self.get_task_title(task_id));
+        let event = Event::MoveTask { task_id, new_parent };
+        self.record(operation_description, event, |task_manager| task_manager.set_parent(task_id, new_parent))
+    }
+
+    /// Reverts the last task operation, returning its description or `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Result<Option<String>, TaskError> {
+        let operation = match self.tasks_state.history.next_undo() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        self.check_restored_states(operation.changes.iter().map(|change| &change.before))?;
+
+        let operation = self.tasks_state.history.undo().unwrap();
+        operation.revert(&mut self.tasks_state.tasks, &mut self.tasks_state.tags, &mut self.tasks_state.priorities);
+        let description = operation.description.clone();
+        let changed_tasks = operation.changes.iter().map(|change| change.task_id).collect();
+        let events = task_event::task_events(&operation.changes, true, &self.tasks_state.tasks);
+        self.register_task_tags();
+        self.persist(Event::Undo, changed_tasks)?;
+        self.notify(events);
+        Ok(Some(description))
+    }
+
+    /// Applies again the last undone task operation, returning its description or `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Result<Option<String>, TaskError> {
+        let operation = match self.tasks_state.history.next_redo() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        self.check_restored_states(operation.changes.iter().map(|change| &change.after))?;
+
+        let operation = self.tasks_state.history.redo().unwrap();
+        operation.apply(&mut self.tasks_state.tasks, &mut self.tasks_state.tags, &mut self.tasks_state.priorities);
+        let description = operation.description.clone();
+        let changed_tasks = operation.changes.iter().map(|change| change.task_id).collect();
+        let events = task_event::task_events(&operation.changes, false, &self.tasks_state.tasks);
+        self.register_task_tags();
+        self.persist(Event::Redo, changed_tasks)?;
+        self.notify(events);
+        Ok(Some(description))
+    }
+
+    //Runs an operation on the tasks, recording what changed so it can be undone
+    fn record<R>(&mut self, description: String, event: Event, operation: impl FnOnce(&mut TaskManager) -> Result<R, TaskError>) -> Result<R, TaskError> {
+        let tasks_before = self.tasks_state.tasks.clone();
+        let tags_before = self.tasks_state.tags.clone();
+        let priorities_before = self.tasks_state.priorities.clone();
+        let result = operation(self)?;
 
-        if !self.tasks_state.tasks.contains_key(parent) {
-            return None;
+        let changes = history::diff_tasks(&tasks_before, &self.tasks_state.tasks);
+        let tag_changes = history::diff_tags(&tags_before, &self.tasks_state.tags);
+        let priority_change = history::diff_priorities(&priorities_before, &self.tasks_state.priorities);
+        let changed_tasks = changes.iter().map(|change| change.task_id).collect();
+        let events = task_event::task_events(&changes, false, &self.tasks_state.tasks);
+        let operation = Operation { description, changes, tag_changes, priority_change };
+        if !operation.is_empty() {
+            self.tasks_state.history.record(operation);
         }
-        
-        
-        if let Some(added_task) = self.add_task(title, state, description) {
-            {
-                let added_task_mut = self.tasks_state.tasks.get_mut(&added_task).unwrap();
-                added_task_mut.parent_task = Some(*parent);
+        self.register_task_tags();
+        self.persist(event, changed_tasks)?;
+        self.notify(events);
+        Ok(result)
+    }
+
+    fn remove_task(&mut self, task_id: TaskId, delete_mode: DeleteMode) -> Result<(), TaskError> {
+        let deleted_task = self.tasks_state.tasks.remove(&task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+
+        let mut orphan_tasks = Vec::new();
+        match delete_mode {
+            DeleteMode::DeleteChildren => {
+                deleted_task.child_tasks.iter().for_each(|child| self.remove_subtree(*child));
+            }
+            DeleteMode::ReparentChildren => {
+                for child in &deleted_task.child_tasks {
+                    if let Some(child_task) = self.tasks_state.tasks.get_mut(child) {
+                        child_task.parent_task = deleted_task.parent_task;
+                        orphan_tasks.push(*child);
+                    }
+                }
             }
-            {
-                let parent_task_opt = self.tasks_state.tasks.get_mut(&parent);
-                if let Some(parent_task) = parent_task_opt {
-                    parent_task.child_tasks.push(added_task);
-                    
+        }
+
+        if let Some(parent) = deleted_task.parent_task {
+            if let Some(parent_task) = self.tasks_state.tasks.get_mut(&parent) {
+                //Children take the place of the deleted task so the order is kept
+                let position = parent_task.child_tasks.iter().position(|child| *child == task_id);
+                if let Some(position) = position {
+                    parent_task.child_tasks.splice(position..position + 1, orphan_tasks);
                 }
             }
-            self.save();
-            return Some(added_task);
         }
-        else {
-            return None;
+        Ok(())
+    }
+
+    /// Fails with the error `move_task` would give, without moving the task.
+    pub fn check_move(&self, task_id: TaskId, new_parent: Option<TaskId>) -> Result<(), TaskError> {
+        let old_parent = self.find_task(task_id).ok_or(TaskError::TaskNotFound(task_id))?.parent_task;
+        match new_parent {
+            Some(new_parent) if old_parent != Some(new_parent) => {
+                if !self.tasks_state.tasks.contains_key(&new_parent) {
+                    return Err(TaskError::ParentNotFound(new_parent));
+                }
+                if self.is_same_or_descendant(new_parent, task_id) {
+                    return Err(TaskError::CyclicParent { task: task_id, parent: new_parent });
+                }
+                Ok(())
+            }
+            _ => Ok(()),
         }
     }
 
+    fn set_parent(&mut self, task_id: TaskId, new_parent: Option<TaskId>) -> Result<(), TaskError> {
+        self.check_move(task_id, new_parent)?;
+        let old_parent = self.find_task(task_id).unwrap().parent_task;
+        if old_parent == new_parent {
+            return Ok(());
+        }
+
+        if let Some(old_parent_task) = old_parent.and_then(|parent| self.tasks_state.tasks.get_mut(&parent)) {
+            old_parent_task.child_tasks.retain(|child| *child != task_id);
+        }
+        if let Some(new_parent_task) = new_parent.and_then(|parent| self.tasks_state.tasks.get_mut(&parent)) {
+            new_parent_task.child_tasks.push(task_id);
+        }
+        self.tasks_state.tasks.get_mut(&task_id).unwrap().parent_task = new_parent;
+        Ok(())
+    }
 
     pub fn get_tasks(&self) -> Vec<&Task> {
         self.tasks_state.tasks.iter().map(|kv| kv.1).collect()
@@ -120,20 +449,460 @@ impl TaskManager {
         self.tasks_state.tasks.get(&task_id)
     }
 
-    pub fn add_state(&mut self, state: String) {
-        if self.tasks_state.valid_states.contains(&state) {
-            return;
+    /// Every task with its depth, each one followed by its subtasks.
+    pub fn get_task_tree(&self) -> Vec<(&Task, usize)> {
+        let mut task_tree = Vec::new();
+        for task in self.tasks_state.tasks.values().filter(|task| task.parent_task.is_none()) {
+            self.add_to_task_tree(task, 0, &mut task_tree);
+        }
+        task_tree
+    }
+
+    fn add_to_task_tree<'a>(&'a self, task: &'a Task, depth: usize, task_tree: &mut Vec<(&'a Task, usize)>) {
+        task_tree.push((task, depth));
+        for child_task in task.child_tasks.iter().filter_map(|child| self.tasks_state.tasks.get(child)) {
+            self.add_to_task_tree(child_task, depth + 1, task_tree);
+        }
+    }
+
+    pub fn add_state(&mut self, state: String) -> Result<(), TaskError> {
+        self.check_new_state_name(&state)?;
+        self.tasks_state.valid_states.push(state.clone());
+        self.persist(Event::AddState { state }, Vec::new())
+    }
+
+    pub fn rename_state(&mut self, state: &str, new_name: String) -> Result<(), TaskError> {
+        self.check_new_state_name(&new_name)?;
+        let index = self.find_state_position(state)?;
+
+        self.tasks_state.valid_states[index] = new_name.clone();
+        let changed_tasks = self.find_tasks_in_state(state);
+        changed_tasks
+            .iter()
+            .for_each(|task_id| self.tasks_state.tasks.get_mut(task_id).unwrap().state = new_name.clone());
+        let workflow = &mut self.tasks_state.workflow;
+        for transition in workflow.transitions.iter_mut() {
+            if transition.from == state {
+                transition.from = new_name.clone();
+            }
+            if transition.to == state {
+                transition.to = new_name.clone();
+            }
+        }
+        workflow.final_states
+            .iter_mut()
+            .filter(|final_state| *final_state == state)
+            .for_each(|final_state| *final_state = new_name.clone());
+        self.tasks_state.history.rename_state(state, &new_name);
+        self.persist(Event::RenameState { state: state.to_string(), new_name }, changed_tasks)
+    }
+
+    /// Deletes a state moving every task in it to `migration_state`, which has to be another valid state.
+    pub fn delete_state(&mut self, state: &str, migration_state: &str) -> Result<(), TaskError> {
+        self.find_state_position(state)?;
+        self.find_state_position(migration_state)?;
+        if state == migration_state {
+            return Err(TaskError::InvalidState(migration_state.to_string()));
+        }
+
+        self.tasks_state.valid_states.retain(|valid_state| valid_state != state);
+        let changed_tasks = self.find_tasks_in_state(state);
+        changed_tasks
+            .iter()
+            .for_each(|task_id| self.tasks_state.tasks.get_mut(task_id).unwrap().state = migration_state.to_string());
+        let workflow = &mut self.tasks_state.workflow;
+        workflow.transitions.retain(|transition| transition.from != state && transition.to != state);
+        workflow.final_states.retain(|final_state| final_state != state);
+        let events = changed_tasks
+            .iter()
+            .filter_map(|task_id| TaskView::find(self, *task_id))
+            .map(|task| TaskEvent::StateChanged { task, previous_state: state.to_string() })
+            .collect();
+        let event = Event::DeleteState { state: state.to_string(), migration_state: migration_state.to_string() };
+        self.persist(event, changed_tasks)?;
+        self.notify(events);
+        Ok(())
+    }
+
+    pub fn move_state(&mut self, state: &str, new_index: usize) -> Result<(), TaskError> {
+        let index = self.find_state_position(state)?;
+        let valid_states = &mut self.tasks_state.valid_states;
+        if new_index >= valid_states.len() {
+            return Err(TaskError::InvalidStatePosition(new_index));
+        }
+
+        let moved_state = valid_states.remove(index);
+        valid_states.insert(new_index, moved_state);
+        self.persist(Event::MoveState { state: state.to_string(), new_index }, Vec::new())
+    }
+
+    /// Every tag in the registry with its color, in alphabetical order.
+    pub fn get_tags(&self) -> &BTreeMap<String, TagColor> {
+        &self.tasks_state.tags
+    }
+
+    /// Sets the color of a tag, adding it to the registry if it is new.
+    pub fn set_tag_color(&mut self, tag: &str, color: TagColor) -> Result<(), TaskError> {
+        if !tag::is_valid_tag_name(tag) {
+            return Err(TaskError::InvalidTagName(tag.to_string()));
+        }
+
+        self.tasks_state.tags.insert(tag.to_string(), color);
+        self.persist(Event::SetTagColor { tag: tag.to_string(), color }, Vec::new())
+    }
+
+    /// Removes a tag from the registry and from every task that has it.
+    pub fn delete_tag(&mut self, tag: &str) -> Result<(), TaskError> {
+        if !self.tasks_state.tags.contains_key(tag) {
+            return Err(TaskError::TagNotFound(tag.to_string()));
+        }
+
+        let operation_description = format!("Delete tag {}", tag);
+        self.record(operation_description, Event::DeleteTag { tag: tag.to_string() }, |task_manager| {
+            task_manager.tasks_state.tasks.values_mut().for_each(|task| {
+                task.tags.remove(tag);
+            });
+            task_manager.tasks_state.tags.remove(tag);
+            Ok(())
+        })
+    }
+
+    /// Priorities tasks can have, from the highest to the lowest.
+    pub fn get_priorities(&self) -> &Vec<Priority> {
+        &self.tasks_state.priorities
+    }
+
+    pub fn find_priority(&self, name: &str) -> Option<&Priority> {
+        self.tasks_state.priorities.iter().find(|priority| priority.name == name)
+    }
+
+    /// Adds a priority below the lowest one.
+    pub fn add_priority(&mut self, priority: String, color: TagColor) -> Result<(), TaskError> {
+        self.check_new_priority_name(&priority)?;
+
+        let operation_description = format!("Add priority {}", priority);
+        let event = Event::AddPriority { priority: priority.clone(), color };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.tasks_state.priorities.push(Priority::new(&priority, color));
+            Ok(())
+        })
+    }
+
+    /// Renames a priority, also in every task that has it.
+    pub fn rename_priority(&mut self, priority: &str, new_name: String) -> Result<(), TaskError> {
+        self.check_new_priority_name(&new_name)?;
+        let index = self.find_priority_position(priority)?;
+
+        let operation_description = format!("Rename priority {} to {}", priority, new_name);
+        let event = Event::RenamePriority { priority: priority.to_string(), new_name: new_name.clone() };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.tasks_state.priorities[index].name = new_name.clone();
+            task_manager.tasks_state.tasks
+                .values_mut()
+                .filter(|task| task.priority.as_deref() == Some(priority))
+                .for_each(|task| task.priority = Some(new_name.clone()));
+            Ok(())
+        })
+    }
+
+    pub fn set_priority_color(&mut self, priority: &str, color: TagColor) -> Result<(), TaskError> {
+        let index = self.find_priority_position(priority)?;
+
+        let operation_description = format!("Color priority {} {}", priority, color);
+        let event = Event::SetPriorityColor { priority: priority.to_string(), color };
+        self.record(operation_description, event, |task_manager| {
+            task_manager.tasks_state.priorities[index].color = color;
+            Ok(())
+        })
+    }
+
+    /// Moves a priority to `new_index`, 0 being the highest.
+    pub fn move_priority(&mut self, priority: &str, new_index: usize) -> Result<(), TaskError> {
+        let index = self.find_priority_position(priority)?;
+        if new_index >= self.tasks_state.priorities.len() {
+            return Err(TaskError::InvalidPriorityPosition(new_index));
+        }
+
+        let operation_description = format!("Move priority {} to position {}", priority, new_index + 1);
+        let event = Event::MovePriority { priority: priority.to_string(), new_index };
+        self.record(operation_description, event, |task_manager| {
+            let priorities = &mut task_manager.tasks_state.priorities;
+            let moved_priority = priorities.remove(index);
+            priorities.insert(new_index, moved_priority);
+            Ok(())
+        })
+    }
+
+    /// Removes a priority, tasks that had it are left without one.
+    pub fn delete_priority(&mut self, priority: &str) -> Result<(), TaskError> {
+        let index = self.find_priority_position(priority)?;
+
+        let operation_description = format!("Delete priority {}", priority);
+        self.record(operation_description, Event::DeletePriority { priority: priority.to_string() }, |task_manager| {
+            task_manager.tasks_state.priorities.remove(index);
+            task_manager.tasks_state.tasks
+                .values_mut()
+                .filter(|task| task.priority.as_deref() == Some(priority))
+                .for_each(|task| task.priority = None);
+            Ok(())
+        })
+    }
+
+    /// Orders tasks from the highest priority to the lowest, keeping the order of tasks with the same one.
+    /// Tasks without a priority, or with one that is no longer valid, go last.
+    pub fn sort_by_priority(&self, task_ids: &mut [TaskId]) {
+        let priorities = &self.tasks_state.priorities;
+        task_ids.sort_by_key(|task_id| {
+            self.find_task(*task_id)
+                .and_then(|task| task.priority.as_ref())
+                .and_then(|task_priority| priorities.iter().position(|priority| priority.name == *task_priority))
+                .unwrap_or(priorities.len())
+        });
+    }
+
+    /// Whether the task is past its due date or close to it. Tasks in a final state are never due.
+    pub fn get_due_status(&self, task: &Task, now: DateTime<Utc>) -> DueStatus {
+        match task.due_date {
+            _ if self.tasks_state.workflow.is_final_state(&task.state) => DueStatus::NotDue,
+            Some(due_date) if due_date <= now => DueStatus::Overdue,
+            Some(due_date) if due_date <= now + Duration::hours(dates::DUE_SOON_HOURS) => DueStatus::DueSoon,
+            _ => DueStatus::NotDue,
+        }
+    }
+
+    /// Starts tracking the time spent on a task from `time`. Only one timer runs at a time, so the running one
+    /// is stopped.
+    pub fn start_timer(&mut self, task_id: TaskId, time: DateTime<Utc>) -> Result<(), TaskError> {
+        let operation_description = format!("Start the timer of \"{}\"", self.get_task_title(task_id));
+        self.record(operation_description, Event::StartTimer { task_id, time }, |task_manager| {
+            let task = task_manager.find_task(task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+            if task.running_entry().is_some() {
+                return Err(TaskError::TimerAlreadyRunning(task_id));
+            }
+
+            task_manager.tasks_state.tasks.values_mut().for_each(|task| close_running_entry(task, time));
+            let task = task_manager.tasks_state.tasks.get_mut(&task_id).unwrap();
+            task.time_entries.push(TimeEntry { start: time, end: None });
+            Ok(())
+        })
+    }
+
+    pub fn stop_timer(&mut self, task_id: TaskId, time: DateTime<Utc>) -> Result<(), TaskError> {
+        let operation_description = format!("Stop the timer of \"{}\"", self.get_task_title(task_id));
+        self.record(operation_description, Event::StopTimer { task_id, time }, |task_manager| {
+            let task = task_manager.tasks_state.tasks.get_mut(&task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+            if task.running_entry().is_none() {
+                return Err(TaskError::TimerNotRunning(task_id));
+            }
+
+            close_running_entry(task, time);
+            Ok(())
+        })
+    }
+
+    /// The task whose timer is running, if any.
+    pub fn get_running_timer(&self) -> Option<&Task> {
+        self.tasks_state.tasks.values().find(|task| task.running_entry().is_some())
+    }
+
+    /// Time spent on a task and every one of its subtasks, counting the running timer up to `now`.
+    pub fn get_subtree_time_spent(&self, task_id: TaskId, now: DateTime<Utc>) -> Duration {
+        match self.find_task(task_id) {
+            Some(task) => task.child_tasks
+                .iter()
+                .map(|child| self.get_subtree_time_spent(*child, now))
+                .fold(task.time_spent(now), |total, duration| total + duration),
+            None => Duration::zero(),
         }
-        self.tasks_state.valid_states.push(state);
+    }
+
+    /// Time spent on each task, grouped by the local day it was spent.
+    pub fn get_time_report(&self, now: DateTime<Utc>) -> BTreeMap<NaiveDate, BTreeMap<TaskId, Duration>> {
+        time_tracking::time_report(self.tasks_state.tasks.values(), now, &Local)
+    }
+
+    pub fn count_tasks_in_state(&self, state: &str) -> usize {
+        self.tasks_state.tasks.values().filter(|task| task.state == state).count()
     }
 
     pub fn get_states(&self) -> &Vec<String> {
         &self.tasks_state.valid_states
     }
 
+    pub fn can_change_state(&self, task_id: TaskId, state: &str) -> bool {
+        self.check_state_change(task_id, state).is_ok()
+    }
+
+    /// Valid states the task can be moved to, in the configured order. Includes its current state.
+    pub fn get_allowed_states(&self, task_id: TaskId) -> Vec<String> {
+        self.tasks_state.valid_states
+            .iter()
+            .filter(|state| self.can_change_state(task_id, state))
+            .cloned()
+            .collect()
+    }
+
+    fn insert_task(&mut self, task_id: TaskId, title: String, state: String, description: String, parent: Option<TaskId>) -> Result<TaskId, TaskError> {
+        if self.tasks_state.tasks.contains_key(&task_id) {
+            return Err(TaskError::TaskAlreadyExists(task_id));
+        }
+        self.find_state_position(&state)?;
+        if let Some(parent) = parent {
+            if !self.tasks_state.tasks.contains_key(&parent) {
+                return Err(TaskError::ParentNotFound(parent));
+            }
+        }
+
+        self.tasks_state.next_valid_id = self.tasks_state.next_valid_id.max(task_id.0 + 1);
+        self.tasks_state.tasks.insert(task_id, Task {
+            id: task_id,
+            title,
+            state,
+            description,
+            parent_task: parent,
+            child_tasks: Vec::new(),
+            tags: BTreeSet::new(),
+            priority: None,
+            due_date: None,
+            start_date: None,
+            time_entries: Vec::new(),
+        });
+        if let Some(parent_task) = parent.and_then(|parent| self.tasks_state.tasks.get_mut(&parent)) {
+            parent_task.child_tasks.push(task_id);
+        }
+        Ok(task_id)
+    }
+
+    fn check_state_change(&self, task_id: TaskId, state: &str) -> Result<(), TaskError> {
+        let task = self.find_task(task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+        self.find_state_position(state)?;
+
+        let workflow = &self.tasks_state.workflow;
+        if !workflow.is_transition_allowed(&task.state, state) {
+            return Err(TaskError::TransitionNotAllowed { from: task.state.clone(), to: state.to_string() });
+        }
+        if workflow.requires_final_children(&task.state, state) && !self.are_subtasks_final(task) {
+            return Err(TaskError::UnfinishedSubtasks { from: task.state.clone(), to: state.to_string() });
+        }
+        Ok(())
+    }
+
+    //Tasks brought back by undo or redo may be in states that were deleted since
+    fn check_restored_states<'a>(&self, mut restored_tasks: impl Iterator<Item = &'a Option<Task>>) -> Result<(), TaskError> {
+        match restored_tasks.find_map(|task| task.as_ref().filter(|task| self.find_state_position(&task.state).is_err())) {
+            Some(task) => Err(TaskError::InvalidState(task.state.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn check_priority(&self, priority: &Option<String>) -> Result<(), TaskError> {
+        match priority {
+            Some(priority) if self.find_priority(priority).is_none() => Err(TaskError::InvalidPriority(priority.clone())),
+            _ => Ok(()),
+        }
+    }
+
+    //New tags get the next color of the palette
+    fn register_task_tags(&mut self) {
+        let tags = &mut self.tasks_state.tags;
+        for tag in self.tasks_state.tasks.values().flat_map(|task| &task.tags) {
+            if !tags.contains_key(tag) {
+                let color = TagColor::ALL[tags.len() % TagColor::ALL.len()];
+                tags.insert(tag.clone(), color);
+            }
+        }
+    }
+
+    fn find_tasks_in_state(&self, state: &str) -> Vec<TaskId> {
+        self.tasks_state.tasks
+            .values()
+            .filter(|task| task.state == state)
+            .map(|task| task.id)
+            .collect()
+    }
+
+    fn get_task_title(&self, task_id: TaskId) -> String {
+        self.find_task(task_id).map(|task| task.title.clone()).unwrap_or_default()
+    }
+
+    fn check_new_state_name(&self, state: &str) -> Result<(), TaskError> {
+        if state.is_empty() {
+            return Err(TaskError::EmptyStateName);
+        }
+        if self.tasks_state.valid_states.iter().any(|valid_state| valid_state == state) {
+            return Err(TaskError::StateAlreadyExists(state.to_string()));
+        }
+        Ok(())
+    }
+
+    fn find_state_position(&self, state: &str) -> Result<usize, TaskError> {
+        self.tasks_state.valid_states
+            .iter()
+            .position(|valid_state| valid_state == state)
+            .ok_or_else(|| TaskError::InvalidState(state.to_string()))
+    }
+
+    fn check_new_priority_name(&self, priority: &str) -> Result<(), TaskError> {
+        if priority.is_empty() {
+            return Err(TaskError::EmptyPriorityName);
+        }
+        if self.find_priority(priority).is_some() {
+            return Err(TaskError::PriorityAlreadyExists(priority.to_string()));
+        }
+        Ok(())
+    }
+
+    fn find_priority_position(&self, priority: &str) -> Result<usize, TaskError> {
+        self.tasks_state.priorities
+            .iter()
+            .position(|valid_priority| valid_priority.name == priority)
+            .ok_or_else(|| TaskError::InvalidPriority(priority.to_string()))
+    }
+
+    fn are_subtasks_final(&self, task: &Task) -> bool {
+        task.child_tasks.iter().all(|child| {
+            self.tasks_state.tasks.get(child).is_none_or(|child_task| {
+                self.tasks_state.workflow.is_final_state(&child_task.state) && self.are_subtasks_final(child_task)
+            })
+        })
+    }
+
+    fn is_same_or_descendant(&self, task_id: TaskId, ancestor: TaskId) -> bool {
+        let mut current = Some(task_id);
+        while let Some(current_id) = current {
+            if current_id == ancestor {
+                return true;
+            }
+            current = self.tasks_state.tasks.get(&current_id).and_then(|task| task.parent_task);
+        }
+        false
+    }
+
+    fn remove_subtree(&mut self, task_id: TaskId) {
+        if let Some(task) = self.tasks_state.tasks.remove(&task_id) {
+            task.child_tasks.iter().for_each(|child| self.remove_subtree(*child));
+        }
+    }
+}
+
+fn check_tag_names(tags: &BTreeSet<String>) -> Result<(), TaskError> {
+    match tags.iter().find(|tag| !tag::is_valid_tag_name(tag)) {
+        Some(tag) => Err(TaskError::InvalidTagName(tag.clone())),
+        None => Ok(()),
+    }
+}
+
+//Entries can not end before they start, even if the clock went back
+fn close_running_entry(task: &mut Task, time: DateTime<Utc>) {
+    if let Some(entry) = task.time_entries.last_mut().filter(|entry| entry.is_running()) {
+        entry.end = Some(time.max(entry.start));
+    }
+}
 
-    fn is_task_valid(&self, task: &Task) -> bool {
-        self.tasks_state.valid_states.contains(&task.state)
+fn check_dates(fields: &TaskFields) -> Result<(), TaskError> {
+    match (fields.start_date, fields.due_date) {
+        (Some(start_date), Some(due_date)) if start_date > due_date => Err(TaskError::StartAfterDue),
+        _ => Ok(()),
     }
 }
 
@@ -141,18 +910,34 @@ impl TaskManager {
 mod tests {
 
     use super::*;
+    use crate::backend::{
+        backups, journal, migrations, priority,
+        storage::{DirectoryStorage, JsonFileStorage, MemoryStorage, SqliteStorage},
+        workflow::Transition,
+    };
+    use std::{
+        path::Path,
+        sync::{Arc, Mutex},
+    };
 
     fn create_task_manager() -> TaskManager {
-        let mut task_manager = TaskManager::new(None);
-        task_manager.initialize();
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
         task_manager
     }
 
+    fn create_test_directory(name: &str) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join(format!("tant-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
     #[test]
     fn add_task() {
         let mut task_manager = create_task_manager();
         let task = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description"));
-        assert_eq!(task.is_some(), true);
+        assert!(task.is_ok());
     }
 
     #[test]
@@ -163,24 +948,42 @@ mod tests {
 
         let task1 = task_manager.find_task(task1_id).unwrap();
         let task2 = task_manager.find_task(task2_id).unwrap();
-        assert_eq!(task1.parent_task.is_none(), true);
+        assert!(task1.parent_task.is_none());
         assert_eq!(task1.child_tasks.len(), 1);
-        assert_eq!(task1.child_tasks.contains(&task2.id), true);
-        assert_eq!(task2.parent_task == Some(task1_id), true);
+        assert!(task1.child_tasks.contains(&task2.id));
+        assert_eq!(task2.parent_task, Some(task1_id));
     }
 
     #[test]
     fn add_task_wrong_state() {
         let mut task_manager = create_task_manager();
         let task = task_manager.add_task(String::from("Title"), String::from("invalid"), String::from("Description"));
-        assert_eq!(task.is_none(), true);
+        assert!(matches!(task, Err(TaskError::InvalidState(_))));
+        assert!(task_manager.get_tasks().is_empty());
+    }
+
+    #[test]
+    fn add_task_with_not_found_parent() {
+        let mut task_manager = create_task_manager();
+        let task = task_manager.add_task_with_parent(String::from("Title"), String::from("Open"), String::from("Description"), &TaskId(7));
+        assert!(matches!(task, Err(TaskError::ParentNotFound(TaskId(7)))));
+        assert!(task_manager.get_tasks().is_empty());
+    }
+
+    #[test]
+    fn add_task_save_error() {
+        let file_path = std::env::temp_dir().join("tant-missing-directory").join("state.json");
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.to_string_lossy().to_string())));
+        task_manager.initialize().unwrap();
+        let task = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description"));
+        assert!(matches!(task, Err(TaskError::Io(_))));
     }
 
     #[test]
     fn find_task_not_found_task() {
         let task_manager = create_task_manager();
         let task = task_manager.find_task(TaskId(1));
-        assert_eq!(task.is_none(), true);
+        assert!(task.is_none());
     }
 
     #[test]
@@ -188,14 +991,1140 @@ mod tests {
         let mut task_manager = create_task_manager();
         let added_task = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description"));
         let found_task = task_manager.find_task(added_task.unwrap());
-        assert_eq!(found_task.is_some(), true);
+        assert!(found_task.is_some());
     }
 
     #[test]
     fn add_state() {
         let mut task_manager = create_task_manager();
-        task_manager.add_state(String::from("Foo"));
+        assert!(task_manager.add_state(String::from("Foo")).is_ok());
         let added_task = task_manager.add_task(String::from("Title"), String::from("Foo"), String::from("Description"));
-        assert_eq!(added_task.is_some(), true);
+        assert!(added_task.is_ok());
+    }
+
+    #[test]
+    fn add_state_already_existing() {
+        let mut task_manager = create_task_manager();
+        assert!(matches!(task_manager.add_state(String::from("Open")), Err(TaskError::StateAlreadyExists(_))));
+        assert_eq!(task_manager.get_states().len(), 4);
+    }
+
+    #[test]
+    fn rename_state() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+        set_transitions(&mut task_manager, &[("Open", "Done", false)]);
+
+        assert!(task_manager.rename_state("Open", String::from("Backlog")).is_ok());
+        assert_eq!(task_manager.get_states()[0], "Backlog");
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "Backlog");
+        assert!(task_manager.can_change_state(task_id, "Done"));
+    }
+
+    #[test]
+    fn rename_state_to_existing_state() {
+        let mut task_manager = create_task_manager();
+        assert!(matches!(task_manager.rename_state("Open", String::from("Done")), Err(TaskError::StateAlreadyExists(_))));
+        assert!(matches!(task_manager.rename_state("invalid", String::from("Foo")), Err(TaskError::InvalidState(_))));
+    }
+
+    #[test]
+    fn delete_state() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("In progress"), String::from("Description")).unwrap();
+        set_transitions(&mut task_manager, &[("Open", "In progress", false)]);
+
+        assert!(task_manager.delete_state("In progress", "Open").is_ok());
+        assert!(!task_manager.get_states().contains(&String::from("In progress")));
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "Open");
+        assert!(task_manager.tasks_state.workflow.transitions.is_empty());
+    }
+
+    #[test]
+    fn delete_state_with_invalid_migration_state() {
+        let mut task_manager = create_task_manager();
+        assert!(matches!(task_manager.delete_state("Open", "Open"), Err(TaskError::InvalidState(_))));
+        assert!(matches!(task_manager.delete_state("Open", "invalid"), Err(TaskError::InvalidState(_))));
+        assert_eq!(task_manager.get_states().len(), 4);
+    }
+
+    #[test]
+    fn move_state() {
+        let mut task_manager = create_task_manager();
+        assert!(task_manager.move_state("Done", 0).is_ok());
+        assert_eq!(task_manager.get_states()[0], "Done");
+        assert_eq!(task_manager.get_states()[1], "Open");
+        assert!(matches!(task_manager.move_state("Done", 4), Err(TaskError::InvalidStatePosition(4))));
+    }
+
+    #[test]
+    fn update_task() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+        let updated = task_manager.update_task(task_id, String::from("New title"), String::from("Done"), String::from("New description"));
+        assert!(updated.is_ok());
+
+        let task = task_manager.find_task(task_id).unwrap();
+        assert_eq!(task.title, "New title");
+        assert_eq!(task.state, "Done");
+        assert_eq!(task.description, "New description");
+    }
+
+    #[test]
+    fn update_task_wrong_state() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+        let updated = task_manager.update_task(task_id, String::from("New title"), String::from("invalid"), String::from("Description"));
+        assert!(matches!(updated, Err(TaskError::InvalidState(_))));
+        assert_eq!(task_manager.find_task(task_id).unwrap().title, "Title");
+    }
+
+    #[test]
+    fn update_task_not_found_task() {
+        let mut task_manager = create_task_manager();
+        let updated = task_manager.update_task(TaskId(1), String::from("Title"), String::from("Open"), String::from("Description"));
+        assert!(matches!(updated, Err(TaskError::TaskNotFound(TaskId(1)))));
+    }
+
+    #[test]
+    fn set_task_state() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+
+        assert!(task_manager.set_task_state(task_id, String::from("In progress")).is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "In progress");
+        assert!(matches!(task_manager.set_task_state(task_id, String::from("invalid")), Err(TaskError::InvalidState(_))));
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "In progress");
+    }
+
+    fn set_transitions(task_manager: &mut TaskManager, transitions: &[(&str, &str, bool)]) {
+        task_manager.tasks_state.workflow.transitions = transitions
+            .iter()
+            .map(|(from, to, requires_final_children)| Transition {
+                from: from.to_string(),
+                to: to.to_string(),
+                requires_final_children: *requires_final_children,
+            })
+            .collect();
+    }
+
+    fn add_task_tree(task_manager: &mut TaskManager) -> (TaskId, TaskId, TaskId, TaskId) {
+        let root = task_manager.add_task(String::from("Root"), String::from("Open"), String::from("Description")).unwrap();
+        let middle = task_manager.add_task_with_parent(String::from("Middle"), String::from("Open"), String::from("Description"), &root).unwrap();
+        let leaf1 = task_manager.add_task_with_parent(String::from("Leaf 1"), String::from("Open"), String::from("Description"), &middle).unwrap();
+        let leaf2 = task_manager.add_task_with_parent(String::from("Leaf 2"), String::from("Open"), String::from("Description"), &middle).unwrap();
+        (root, middle, leaf1, leaf2)
+    }
+
+    #[test]
+    fn task_tree() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        let other_root = task_manager.add_task(String::from("Other"), String::from("Open"), String::from("")).unwrap();
+        assert!(task_manager.move_task(leaf1, Some(other_root)).is_ok());
+
+        let task_tree: Vec<(TaskId, usize)> = task_manager.get_task_tree().iter().map(|(task, depth)| (task.id, *depth)).collect();
+        assert_eq!(task_tree, vec![(root, 0), (middle, 1), (leaf2, 2), (other_root, 0), (leaf1, 1)]);
+    }
+
+    #[test]
+    fn delete_task_with_children() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+
+        assert!(task_manager.delete_task(middle, DeleteMode::DeleteChildren).is_ok());
+        assert!(task_manager.find_task(middle).is_none());
+        assert!(task_manager.find_task(leaf1).is_none());
+        assert!(task_manager.find_task(leaf2).is_none());
+        assert!(task_manager.find_task(root).unwrap().child_tasks.is_empty());
+    }
+
+    #[test]
+    fn delete_task_reparenting_children() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+
+        assert!(task_manager.delete_task(middle, DeleteMode::ReparentChildren).is_ok());
+        assert!(task_manager.find_task(middle).is_none());
+        assert_eq!(task_manager.find_task(root).unwrap().child_tasks, vec![leaf1, leaf2]);
+        assert_eq!(task_manager.find_task(leaf1).unwrap().parent_task, Some(root));
+        assert_eq!(task_manager.find_task(leaf2).unwrap().parent_task, Some(root));
+    }
+
+    #[test]
+    fn delete_root_task_reparenting_children() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, _, _) = add_task_tree(&mut task_manager);
+
+        assert!(task_manager.delete_task(root, DeleteMode::ReparentChildren).is_ok());
+        assert!(task_manager.find_task(middle).unwrap().parent_task.is_none());
+    }
+
+    #[test]
+    fn delete_task_not_found_task() {
+        let mut task_manager = create_task_manager();
+        assert!(matches!(task_manager.delete_task(TaskId(1), DeleteMode::DeleteChildren), Err(TaskError::TaskNotFound(TaskId(1)))));
+    }
+
+    #[test]
+    fn move_task() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, _) = add_task_tree(&mut task_manager);
+
+        assert!(task_manager.move_task(leaf1, Some(root)).is_ok());
+        assert_eq!(task_manager.find_task(leaf1).unwrap().parent_task, Some(root));
+        assert_eq!(task_manager.find_task(root).unwrap().child_tasks, vec![middle, leaf1]);
+        assert!(!task_manager.find_task(middle).unwrap().child_tasks.contains(&leaf1));
+    }
+
+    #[test]
+    fn move_task_to_top_level() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, _, _) = add_task_tree(&mut task_manager);
+
+        assert!(task_manager.move_task(middle, None).is_ok());
+        assert!(task_manager.find_task(middle).unwrap().parent_task.is_none());
+        assert!(task_manager.find_task(root).unwrap().child_tasks.is_empty());
+    }
+
+    #[test]
+    fn move_task_under_descendant() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, _) = add_task_tree(&mut task_manager);
+
+        assert!(matches!(task_manager.move_task(root, Some(leaf1)), Err(TaskError::CyclicParent { .. })));
+        assert!(matches!(task_manager.move_task(middle, Some(middle)), Err(TaskError::CyclicParent { .. })));
+        assert!(task_manager.find_task(root).unwrap().parent_task.is_none());
+        assert_eq!(task_manager.find_task(middle).unwrap().parent_task, Some(root));
+    }
+
+    #[test]
+    fn move_task_not_found_parent() {
+        let mut task_manager = create_task_manager();
+        let (root, _, _, _) = add_task_tree(&mut task_manager);
+
+        assert!(matches!(task_manager.move_task(root, Some(TaskId(42))), Err(TaskError::ParentNotFound(TaskId(42)))));
+    }
+
+    #[test]
+    fn workflow_without_transitions_allows_everything() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+
+        assert_eq!(task_manager.get_allowed_states(task_id), *task_manager.get_states());
+        assert!(task_manager.set_task_state(task_id, String::from("Done")).is_ok());
+    }
+
+    #[test]
+    fn workflow_transitions() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("Description")).unwrap();
+        set_transitions(&mut task_manager, &[("Open", "In progress", false), ("In progress", "Done", false)]);
+
+        assert_eq!(task_manager.get_allowed_states(task_id), vec![String::from("Open"), String::from("In progress")]);
+        assert!(matches!(task_manager.set_task_state(task_id, String::from("Done")), Err(TaskError::TransitionNotAllowed { .. })));
+        assert!(matches!(task_manager.update_task(task_id, String::from("Title"), String::from("Done"), String::from("Description")), Err(TaskError::TransitionNotAllowed { .. })));
+        assert!(task_manager.set_task_state(task_id, String::from("In progress")).is_ok());
+        assert!(task_manager.set_task_state(task_id, String::from("Done")).is_ok());
+    }
+
+    #[test]
+    fn workflow_transition_requiring_final_children() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        set_transitions(&mut task_manager, &[("Open", "Done", true)]);
+
+        assert!(matches!(task_manager.set_task_state(root, String::from("Done")), Err(TaskError::UnfinishedSubtasks { .. })));
+        assert!(task_manager.set_task_state(leaf1, String::from("Done")).is_ok());
+        assert!(task_manager.set_task_state(leaf2, String::from("Done")).is_ok());
+        assert!(matches!(task_manager.set_task_state(root, String::from("Done")), Err(TaskError::UnfinishedSubtasks { .. })));
+        assert!(task_manager.set_task_state(middle, String::from("Done")).is_ok());
+        assert!(task_manager.set_task_state(root, String::from("Done")).is_ok());
+    }
+
+    #[test]
+    fn initialize_missing_file() {
+        let directory = create_test_directory("initialize-missing-file");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+
+        assert!(task_manager.initialize().is_ok());
+        assert_eq!(task_manager.get_states().len(), 4);
+    }
+
+    #[test]
+    fn initialize_corrupted_file() {
+        let directory = create_test_directory("initialize-corrupted-file");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let content = r#"{"tasks":{"0":{"id":0,"title":"Foo","state":"Open","description":"","parent_task":null,"child_tasks":[]}},"valid_states":["Open"],"next_valid_id":1"#;
+        fs::write(&file_path, content).unwrap();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+
+        match task_manager.initialize() {
+            Err(TaskError::CorruptedFile { path, backup_path, error }) => {
+                assert_eq!(path, file_path);
+                assert_eq!(backup_path, format!("{}.corrupt", file_path));
+                assert_eq!(fs::read_to_string(backup_path).unwrap(), content);
+                assert_eq!(error.line(), 1);
+            }
+            _ => panic!("The corrupted file was not reported"),
+        }
+        assert!(task_manager.get_tasks().is_empty());
+        assert_eq!(task_manager.get_states().len(), 4);
+    }
+
+    #[test]
+    fn recover_from_corrupted_file() {
+        let directory = create_test_directory("recover-from-corrupted-file");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let content = concat!(
+            r#"{"tasks":{"0":{"id":0,"title":"Root","state":"Review","description":"","parent_task":null,"child_tasks":[1,2]},"#,
+            r#""1":{"id":1,"title":"Child","state":"Open","description":"","parent_task":0,"child_tasks":[]},"#,
+            r#""2":{"id":2,"title":Broken,"state":"Open","description":"","parent_task":0,"child_tasks":[]},"#,
+            r#""3":{"id":3,"title":"Orphan","state":"Done","description":"","parent_task":2,"child_tasks":[]}},"#,
+            r#""valid_states":["Open","Done"],"next_valid_id":7"#,
+        );
+        fs::write(&file_path, content).unwrap();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        let backup_path = match task_manager.initialize() {
+            Err(TaskError::CorruptedFile { backup_path, .. }) => backup_path,
+            _ => panic!("The corrupted file was not reported"),
+        };
+
+        assert_eq!(task_manager.recover_from(&backup_path).unwrap(), 3);
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().child_tasks, vec![TaskId(1)]);
+        assert_eq!(task_manager.find_task(TaskId(1)).unwrap().parent_task, Some(TaskId(0)));
+        assert!(task_manager.find_task(TaskId(2)).is_none());
+        assert!(task_manager.find_task(TaskId(3)).unwrap().parent_task.is_none());
+        assert_eq!(*task_manager.get_states(), vec![String::from("Open"), String::from("Done"), String::from("Review")]);
+        assert_eq!(task_manager.add_task(String::from("New"), String::from("Open"), String::from("")).unwrap(), TaskId(7));
+
+        let mut reloaded_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+        assert!(reloaded_task_manager.initialize().is_ok());
+        assert_eq!(reloaded_task_manager.get_tasks().len(), 4);
+    }
+
+    #[test]
+    fn recover_without_history_tasks() {
+        let directory = create_test_directory("recover-without-history-tasks");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let deleted_task = r#"{"id":5,"title":"Deleted","state":"Open","description":"","parent_task":null,"child_tasks":[]}"#;
+        let old_task = r#"{"id":0,"title":"Old title","state":"Open","description":"","parent_task":null,"child_tasks":[]}"#;
+        let tasks = concat!(
+            r#""tasks":{"0":{"id":0,"title":"Root","state":"Open","description":"","parent_task":null,"child_tasks":[]},"#,
+            r#""1":{"id":1,"title":Broken,"state":"Open","description":"","parent_task":null,"child_tasks":[]}},"#,
+        );
+        let history = format!(
+            r#""history":{{"undo_operations":[{{"description":"Delete","changes":[{{"task_id":5,"before":{},"after":null}}]}},{{"description":"Edit","changes":[{{"task_id":0,"before":{},"after":null}}]}}],"redo_operations":[]}},"#,
+            deleted_task, old_task
+        );
+        let broken_history = history.replace(r#""undo_operations""#, "undo_operations");
+        //The history is read before the tasks so an old copy would be kept if it was not skipped
+        for history in [history, broken_history] {
+            let content = format!(r#"{{{}{}"journal_sequence":0,"valid_states":["Open","Done"],"next_valid_id":2}}"#, history, tasks);
+            fs::write(&file_path, content).unwrap();
+            let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+            let backup_path = match task_manager.initialize() {
+                Err(TaskError::CorruptedFile { backup_path, .. }) => backup_path,
+                _ => panic!("The corrupted file was not reported"),
+            };
+
+            assert_eq!(task_manager.recover_from(&backup_path).unwrap(), 1);
+            assert_eq!(task_manager.find_task(TaskId(0)).unwrap().title, "Root");
+            assert!(task_manager.find_task(TaskId(5)).is_none());
+        }
+    }
+
+    #[test]
+    fn save_rotates_backups() {
+        let directory = create_test_directory("save-rotates-backups");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let start_session = || {
+            let mut storage = JsonFileStorage::new(file_path.clone());
+            storage.set_backup_count(2);
+            let mut task_manager = TaskManager::new(Box::new(storage));
+            task_manager.initialize().unwrap();
+            task_manager
+        };
+        let count_backup_tasks = |index| {
+            let mut backup_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(backups::backup_path(&file_path, index))));
+            backup_task_manager.initialize().unwrap();
+            backup_task_manager.get_tasks().len()
+        };
+        for (session, titles) in [["First", "Second"], ["Third", "Fourth"], ["Fifth", "Sixth"], ["Seventh", "Eighth"]].iter().enumerate() {
+            let mut task_manager = start_session();
+            for title in titles {
+                assert!(task_manager.add_task(String::from(*title), String::from("Open"), String::from("")).is_ok());
+            }
+            //Several saves in a session only keep the state it started with
+            if session == 1 {
+                assert_eq!(backups::list_backups(&file_path), vec![1]);
+                assert_eq!(count_backup_tasks(1), 2);
+            }
+        }
+
+        assert_eq!(backups::list_backups(&file_path), vec![1, 2]);
+        assert!(!Path::new(&format!("{}.tmp", file_path)).exists());
+        assert_eq!(count_backup_tasks(1), 6);
+        assert_eq!(count_backup_tasks(2), 4);
+    }
+
+    #[test]
+    fn restore_backup() {
+        let directory = create_test_directory("restore-backup");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        task_manager.initialize().unwrap();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+        //Backups are kept once per session
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        task_manager.initialize().unwrap();
+        assert!(task_manager.delete_task(task_id, DeleteMode::DeleteChildren).is_ok());
+
+        assert!(task_manager.restore_from(&mut JsonFileStorage::new(backups::backup_path(&file_path, 1))).is_ok());
+        assert!(task_manager.find_task(task_id).is_some());
+        let mut reloaded_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        reloaded_task_manager.initialize().unwrap();
+        assert!(reloaded_task_manager.find_task(task_id).is_some());
+
+        //The state before restoring can be restored back
+        assert!(task_manager.restore_from(&mut JsonFileStorage::new(backups::backup_path(&file_path, 1))).is_ok());
+        assert!(task_manager.find_task(task_id).is_none());
+    }
+
+    #[test]
+    fn restore_missing_backup() {
+        let directory = create_test_directory("restore-missing-backup");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        task_manager.initialize().unwrap();
+        let mut backup_storage = JsonFileStorage::new(backups::backup_path(&file_path, 1));
+        assert!(matches!(task_manager.restore_from(&mut backup_storage), Err(TaskError::Io(_))));
+    }
+
+    #[test]
+    fn initialize_original_format() {
+        let directory = create_test_directory("initialize-original-format");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let content = r#"{"tasks":{"0":{"id":0,"title":"Foo","state":"Open","description":"","parent_task":null,"child_tasks":[]}},"valid_states":["Open","Done"],"next_valid_id":1}"#;
+        fs::write(&file_path, content).unwrap();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+
+        assert!(task_manager.initialize().is_ok());
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().title, "Foo");
+        assert_eq!(*task_manager.get_states(), vec![String::from("Open"), String::from("Done")]);
+        assert!(task_manager.save().is_ok());
+        let saved_document: serde_json::Value = serde_json::from_slice(&fs::read(&file_path).unwrap()).unwrap();
+        assert_eq!(saved_document["version"], serde_json::json!(migrations::CURRENT_VERSION));
+    }
+
+    #[test]
+    fn initialize_newer_format() {
+        let directory = create_test_directory("initialize-newer-format");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let content = format!(r#"{{"version":{},"tasks":{{}}}}"#, migrations::CURRENT_VERSION + 1);
+        fs::write(&file_path, &content).unwrap();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+
+        assert!(matches!(task_manager.initialize(), Err(TaskError::UnsupportedVersion(_))));
+        assert!(!Path::new(&format!("{}.corrupt", file_path)).exists());
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), content);
+    }
+
+    #[test]
+    fn undo_add_task() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+
+        assert_eq!(task_manager.undo().unwrap(), Some(String::from("Create \"Title\"")));
+        assert!(task_manager.find_task(task_id).is_none());
+        assert_eq!(task_manager.undo().unwrap(), None);
+        assert_eq!(task_manager.redo().unwrap(), Some(String::from("Create \"Title\"")));
+        assert!(task_manager.find_task(task_id).is_some());
+        assert_eq!(task_manager.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn undo_delete_task() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        assert!(task_manager.delete_task(middle, DeleteMode::DeleteChildren).is_ok());
+
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.find_task(root).unwrap().child_tasks, vec![middle]);
+        assert_eq!(task_manager.find_task(middle).unwrap().child_tasks, vec![leaf1, leaf2]);
+        assert_eq!(task_manager.find_task(leaf2).unwrap().parent_task, Some(middle));
+    }
+
+    #[test]
+    fn undo_edit_move_and_state_change() {
+        let mut task_manager = create_task_manager();
+        let (root, middle, leaf1, _) = add_task_tree(&mut task_manager);
+        assert!(task_manager.update_task(leaf1, String::from("Edited"), String::from("Open"), String::from("Edited")).is_ok());
+        assert!(task_manager.move_task(leaf1, Some(root)).is_ok());
+        assert!(task_manager.set_task_state(leaf1, String::from("Done")).is_ok());
+
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.find_task(leaf1).unwrap().state, "Open");
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.find_task(leaf1).unwrap().parent_task, Some(middle));
+        assert_eq!(task_manager.find_task(root).unwrap().child_tasks, vec![middle]);
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.find_task(leaf1).unwrap().title, "Leaf 1");
+
+        assert!(task_manager.redo().is_ok());
+        assert!(task_manager.redo().is_ok());
+        assert_eq!(task_manager.find_task(leaf1).unwrap().parent_task, Some(root));
+        assert_eq!(task_manager.find_task(leaf1).unwrap().title, "Edited");
+    }
+
+    #[test]
+    fn new_operation_clears_redo() {
+        let mut task_manager = create_task_manager();
+        assert!(task_manager.add_task(String::from("First"), String::from("Open"), String::from("")).is_ok());
+        assert!(task_manager.undo().is_ok());
+        assert!(task_manager.add_task(String::from("Second"), String::from("Open"), String::from("")).is_ok());
+
+        assert_eq!(task_manager.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn failed_operation_is_not_recorded() {
+        let mut task_manager = create_task_manager();
+        assert!(task_manager.add_task(String::from("Title"), String::from("Unknown"), String::from("")).is_err());
+
+        assert_eq!(task_manager.undo().unwrap(), None);
+    }
+
+    #[test]
+    fn undo_after_state_changes() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("In progress"), String::from("")).unwrap();
+        assert!(task_manager.set_task_state(task_id, String::from("Done")).is_ok());
+        assert!(task_manager.rename_state("In progress", String::from("Doing")).is_ok());
+
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "Doing");
+
+        assert!(task_manager.redo().is_ok());
+        assert!(task_manager.delete_state("Doing", "Open").is_ok());
+        assert!(matches!(task_manager.undo(), Err(TaskError::InvalidState(state)) if state == "Doing"));
+        assert_eq!(task_manager.find_task(task_id).unwrap().state, "Done");
+    }
+
+    #[test]
+    fn history_is_limited() {
+        let mut task_manager = create_task_manager();
+        for _ in 0..history::HISTORY_LENGTH + 1 {
+            assert!(task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).is_ok());
+        }
+
+        for _ in 0..history::HISTORY_LENGTH {
+            assert!(task_manager.undo().unwrap().is_some());
+        }
+        assert_eq!(task_manager.undo().unwrap(), None);
+        assert_eq!(task_manager.get_tasks().len(), 1);
+    }
+
+    #[test]
+    fn history_is_saved() {
+        let directory = create_test_directory("history-is-saved");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path.clone())));
+        task_manager.initialize().unwrap();
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+
+        let mut reloaded_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+        reloaded_task_manager.initialize().unwrap();
+        assert!(reloaded_task_manager.undo().unwrap().is_some());
+        assert!(reloaded_task_manager.find_task(task_id).is_none());
+    }
+
+    fn create_journal_task_manager(file_path: &str) -> TaskManager {
+        let mut storage = JsonFileStorage::new(file_path.to_string());
+        storage.set_journal_enabled(true);
+        let mut task_manager = TaskManager::new(Box::new(storage));
+        task_manager.initialize().unwrap();
+        task_manager
+    }
+
+    #[test]
+    fn journal_replay() {
+        let directory = create_test_directory("journal-replay");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = create_journal_task_manager(&file_path);
+        let (root, middle, leaf1, _) = add_task_tree(&mut task_manager);
+        assert!(task_manager.rename_state("Open", String::from("To do")).is_ok());
+        assert!(task_manager.move_task(leaf1, Some(root)).is_ok());
+        assert!(task_manager.delete_task(middle, DeleteMode::ReparentChildren).is_ok());
+        assert!(task_manager.undo().is_ok());
+
+        let journal = journal::read(&journal::journal_path(&file_path)).unwrap();
+        assert_eq!(journal.entries.len(), 8);
+        assert_eq!(journal.entries[7].0, 8);
+        assert_eq!(journal.entries[7].1.event, Event::Undo);
+        //Only the first change was written to the file
+        let snapshot: serde_json::Value = serde_json::from_slice(&fs::read(&file_path).unwrap()).unwrap();
+        assert_eq!(snapshot["journal_sequence"], serde_json::json!(1));
+
+        let reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        assert_eq!(reloaded_task_manager.get_states(), task_manager.get_states());
+        let mut json_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+        json_task_manager.initialize().unwrap();
+        assert_eq!(json_task_manager.get_tasks(), task_manager.get_tasks());
+        assert!(json_task_manager.redo().unwrap().is_some());
+    }
+
+    #[test]
+    fn journal_snapshots_and_compaction() {
+        let directory = create_test_directory("journal-snapshots-and-compaction");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = create_journal_task_manager(&file_path);
+        for _ in 0..journal::SNAPSHOT_INTERVAL + 1 {
+            assert!(task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).is_ok());
+        }
+        let snapshot: serde_json::Value = serde_json::from_slice(&fs::read(&file_path).unwrap()).unwrap();
+        assert_eq!(snapshot["journal_sequence"], serde_json::json!(journal::SNAPSHOT_INTERVAL));
+
+        assert_eq!(task_manager.compact_journal().unwrap(), journal::SNAPSHOT_INTERVAL as usize + 1);
+        assert!(journal::read(&journal::journal_path(&file_path)).unwrap().entries.is_empty());
+        assert!(task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).is_ok());
+
+        let reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks().len(), journal::SNAPSHOT_INTERVAL as usize + 2);
+    }
+
+    #[test]
+    fn journal_with_unfinished_line() {
+        let directory = create_test_directory("journal-with-unfinished-line");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let journal_path = journal::journal_path(&file_path);
+        let mut task_manager = create_journal_task_manager(&file_path);
+        assert!(task_manager.add_task(String::from("First"), String::from("Open"), String::from("")).is_ok());
+        let mut content = fs::read_to_string(&journal_path).unwrap();
+        content.push_str(r#"{"sequence":2,"time":"#);
+        fs::write(&journal_path, &content).unwrap();
+
+        //Reading does not change the journal, the line may still be being written
+        let mut reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(fs::read_to_string(&journal_path).unwrap(), content);
+        let journal = journal::read(&journal_path).unwrap();
+        assert!(journal.partial_last_line);
+        assert_eq!(journal.entries.len(), 1);
+
+        assert!(reloaded_task_manager.add_task(String::from("Second"), String::from("Open"), String::from("")).is_ok());
+        let journal = journal::read(&journal_path).unwrap();
+        assert_eq!(journal.entries.len(), 2);
+        assert!(journal.invalid_lines.is_empty());
+        assert!(!journal.partial_last_line);
+    }
+
+    #[test]
+    fn journal_with_invalid_line() {
+        let directory = create_test_directory("journal-with-invalid-line");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let journal_path = journal::journal_path(&file_path);
+        let mut task_manager = create_journal_task_manager(&file_path);
+        assert!(task_manager.add_task(String::from("First"), String::from("Open"), String::from("")).is_ok());
+        assert!(task_manager.add_task(String::from("Second"), String::from("Open"), String::from("")).is_ok());
+        let third = task_manager.add_task(String::from("Third"), String::from("Open"), String::from("")).unwrap();
+        assert!(task_manager.update_task(third, String::from("Edited"), String::from("Open"), String::from("")).is_ok());
+        let content = fs::read_to_string(&journal_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(&journal_path, format!("{}\nbroken\n{}\n{}\n", lines[0], lines[2], lines[3])).unwrap();
+
+        let mut reloaded_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+        assert!(matches!(reloaded_task_manager.initialize(), Err(TaskError::CorruptedJournal { line: 2, .. })));
+        //The tasks after the skipped one keep their ids, so the edit still applies to the third task
+        let tasks: Vec<(TaskId, &str)> = reloaded_task_manager.get_tasks().iter().map(|task| (task.id, task.title.as_str())).collect();
+        assert_eq!(tasks, vec![(TaskId(0), "First"), (third, "Edited")]);
+        assert_eq!(reloaded_task_manager.add_task(String::from("Fourth"), String::from("Open"), String::new()).unwrap(), TaskId(3));
+        //Compacting would drop the invalid line
+        assert!(reloaded_task_manager.compact_journal().is_err());
+        assert!(fs::read_to_string(&journal_path).unwrap().contains("broken"));
+    }
+
+    #[test]
+    fn journal_with_taken_task_id() {
+        let directory = create_test_directory("journal-with-taken-task-id");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let journal_path = journal::journal_path(&file_path);
+        let mut task_manager = create_journal_task_manager(&file_path);
+        assert!(task_manager.add_task(String::from("First"), String::from("Open"), String::from("")).is_ok());
+        let event = Event::AddTask {
+            title: String::from("Second"),
+            state: String::from("Open"),
+            description: String::new(),
+            parent: None,
+            task_id: Some(TaskId(0)),
+        };
+        let entry = journal::JournalEntry { sequence: 2, time: Utc::now(), event };
+        let mut content = fs::read_to_string(&journal_path).unwrap();
+        content.push_str("\n\n");
+        fs::write(&journal_path, content).unwrap();
+        assert!(journal::append(&journal_path, &entry).is_ok());
+
+        let mut reloaded_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(file_path)));
+        let initialized = reloaded_task_manager.initialize();
+        //Empty lines are skipped but still counted
+        assert!(matches!(initialized, Err(TaskError::CorruptedJournal { line: 4, message, .. }) if message == "Task 0 already exists"));
+        assert_eq!(reloaded_task_manager.get_tasks().len(), 1);
+    }
+
+    fn create_sqlite_task_manager(file_path: &str) -> TaskManager {
+        let mut task_manager = TaskManager::new(Box::new(SqliteStorage::open(file_path).unwrap()));
+        task_manager.initialize().unwrap();
+        task_manager
+    }
+
+    #[test]
+    fn sqlite_storage() {
+        let directory = create_test_directory("sqlite-storage");
+        let file_path = directory.join("state.db").to_string_lossy().to_string();
+        let mut task_manager = create_sqlite_task_manager(&file_path);
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        assert!(task_manager.rename_state("Open", String::from("To do")).is_ok());
+        assert!(task_manager.delete_task(leaf2, DeleteMode::DeleteChildren).is_ok());
+        assert!(task_manager.move_task(leaf1, Some(root)).is_ok());
+        assert!(task_manager.undo().is_ok());
+
+        let mut reloaded_task_manager = create_sqlite_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        assert_eq!(reloaded_task_manager.get_states(), task_manager.get_states());
+        assert!(reloaded_task_manager.find_task(leaf2).is_none());
+        assert_eq!(reloaded_task_manager.find_task(leaf1).unwrap().parent_task, Some(middle));
+        assert_eq!(reloaded_task_manager.find_task(leaf1).unwrap().state, "To do");
+        assert!(reloaded_task_manager.redo().unwrap().is_some());
+        assert_eq!(reloaded_task_manager.add_task(String::from("New"), String::from("Done"), String::from("")).unwrap(), TaskId(4));
+    }
+
+    #[test]
+    fn sqlite_storage_restore_from_json() {
+        let directory = create_test_directory("sqlite-storage-restore-from-json");
+        let json_file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut json_task_manager = TaskManager::new(Box::new(JsonFileStorage::new(json_file_path.clone())));
+        json_task_manager.initialize().unwrap();
+        add_task_tree(&mut json_task_manager);
+
+        let sqlite_file_path = directory.join("state.db").to_string_lossy().to_string();
+        let mut task_manager = create_sqlite_task_manager(&sqlite_file_path);
+        assert!(task_manager.restore_from(&mut JsonFileStorage::new(json_file_path)).is_ok());
+        let reloaded_task_manager = create_sqlite_task_manager(&sqlite_file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), json_task_manager.get_tasks());
+    }
+
+    fn create_directory_task_manager(directory: &str) -> TaskManager {
+        let mut task_manager = TaskManager::new(Box::new(DirectoryStorage::new(directory)));
+        task_manager.initialize().unwrap();
+        task_manager
+    }
+
+    #[test]
+    fn directory_storage() {
+        let directory = create_test_directory("directory-storage").join("state");
+        let directory_path = directory.to_string_lossy().to_string();
+        let mut task_manager = create_directory_task_manager(&directory_path);
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        assert!(task_manager.rename_state("Open", String::from("To do")).is_ok());
+        assert!(task_manager.delete_task(leaf2, DeleteMode::DeleteChildren).is_ok());
+        assert!(task_manager.move_task(leaf1, Some(root)).is_ok());
+        assert!(task_manager.undo().is_ok());
+
+        assert!(directory.join("tasks").join("0.json").exists());
+        assert!(!directory.join("tasks").join("3.json").exists());
+        let task_file = fs::read_to_string(directory.join("tasks").join("2.json")).unwrap();
+        assert!(task_file.contains("\n  \"title\": \"Leaf 1\""));
+        let index_file = fs::read_to_string(directory.join("index.json")).unwrap();
+        assert!(index_file.contains("\"next_valid_id\": 4") && !index_file.contains("history"));
+
+        let mut reloaded_task_manager = create_directory_task_manager(&directory_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        assert_eq!(reloaded_task_manager.get_states(), task_manager.get_states());
+        assert_eq!(reloaded_task_manager.find_task(leaf1).unwrap().parent_task, Some(middle));
+        assert!(reloaded_task_manager.redo().unwrap().is_some());
+        assert_eq!(reloaded_task_manager.add_task(String::from("New"), String::from("Done"), String::from("")).unwrap(), TaskId(4));
+    }
+
+    #[test]
+    fn directory_storage_after_merge() {
+        let directory = create_test_directory("directory-storage-after-merge").join("state");
+        let directory_path = directory.to_string_lossy().to_string();
+        let mut task_manager = create_directory_task_manager(&directory_path);
+        let (root, _, leaf1, _) = add_task_tree(&mut task_manager);
+
+        //A merged branch added a child to the root without touching the root file or the index
+        let mut merged_task = task_manager.find_task(leaf1).unwrap().clone();
+        merged_task.id = TaskId(7);
+        merged_task.parent_task = Some(root);
+        fs::write(directory.join("tasks").join("7.json"), serde_json::to_string_pretty(&merged_task).unwrap()).unwrap();
+
+        let mut reloaded_task_manager = create_directory_task_manager(&directory_path);
+        assert!(reloaded_task_manager.find_task(root).unwrap().child_tasks.contains(&TaskId(7)));
+        assert_eq!(reloaded_task_manager.add_task(String::from("New"), String::from("Done"), String::from("")).unwrap(), TaskId(8));
+    }
+
+    fn record_events(task_manager: &mut TaskManager) -> Arc<Mutex<Vec<TaskEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded_events = Arc::clone(&events);
+        task_manager.set_event_listener(Box::new(move |event| recorded_events.lock().unwrap().push(event.clone())));
+        events
+    }
+
+    #[test]
+    fn task_events() {
+        let mut task_manager = create_task_manager();
+        let events = record_events(&mut task_manager);
+        let (root, middle, leaf1, leaf2) = add_task_tree(&mut task_manager);
+        assert!(task_manager.set_task_state(leaf1, String::from("Done")).is_ok());
+        assert!(task_manager.move_task(leaf2, Some(root)).is_ok());
+        assert!(task_manager.set_task_state(leaf1, String::from("Unknown")).is_err());
+        assert!(task_manager.delete_task(middle, DeleteMode::DeleteChildren).is_ok());
+        assert!(task_manager.undo().is_ok());
+
+        let events: Vec<(&str, TaskId, usize)> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event.name(), TaskId(event.task().id), event.task().depth))
+            .collect();
+        assert_eq!(events, vec![
+            ("created", root, 0), ("created", middle, 1), ("created", leaf1, 2), ("created", leaf2, 2),
+            ("state_changed", leaf1, 2),
+            ("deleted", middle, 1), ("deleted", leaf1, 2),
+            ("created", middle, 1), ("created", leaf1, 2),
+        ]);
+    }
+
+    #[test]
+    fn state_deletion_events() {
+        let mut task_manager = create_task_manager();
+        let task = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+        let events = record_events(&mut task_manager);
+        assert!(task_manager.delete_state("Open", "Done").is_ok());
+
+        let events = events.lock().unwrap();
+        assert!(matches!(&events[..], [TaskEvent::StateChanged { task: view, previous_state }] if view.id == task.0 && previous_state == "Open"));
+    }
+
+    fn task_fields(title: &str, tags: &[&str]) -> TaskFields {
+        TaskFields {
+            title: String::from(title),
+            state: String::from("Open"),
+            description: String::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            priority: None,
+            due_date: None,
+            start_date: None,
+        }
+    }
+
+    #[test]
+    fn create_task_with_tags() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(task_fields("Title", &["work", "bug"]), None).unwrap();
+        let subtask_id = task_manager.create_task(task_fields("Subtask", &["work"]), Some(task_id)).unwrap();
+
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), task_fields("Title", &["bug", "work"]));
+        assert_eq!(task_manager.find_task(subtask_id).unwrap().parent_task, Some(task_id));
+        let tags: Vec<(&String, &TagColor)> = task_manager.get_tags().iter().collect();
+        assert_eq!(tags, vec![(&String::from("bug"), &TagColor::Red), (&String::from("work"), &TagColor::Green)]);
+    }
+
+    #[test]
+    fn create_task_with_invalid_tag() {
+        let mut task_manager = create_task_manager();
+        let created = task_manager.create_task(task_fields("Title", &["two words"]), None);
+        assert!(matches!(created, Err(TaskError::InvalidTagName(tag)) if tag == "two words"));
+        assert!(task_manager.get_tasks().is_empty());
+        assert!(task_manager.get_tags().is_empty());
+    }
+
+    #[test]
+    fn edit_task_tags() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(task_fields("Title", &["work"]), None).unwrap();
+
+        assert!(task_manager.edit_task(task_id, task_fields("Edited", &["home"])).is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), task_fields("Edited", &["home"]));
+        assert!(task_manager.update_task(task_id, String::from("Updated"), String::from("Open"), String::new()).is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), task_fields("Updated", &["home"]));
+        assert!(matches!(task_manager.edit_task(TaskId(7), task_fields("Title", &[])), Err(TaskError::TaskNotFound(TaskId(7)))));
+    }
+
+    #[test]
+    fn tag_colors() {
+        let mut task_manager = create_task_manager();
+        assert!(task_manager.set_tag_color("work", TagColor::Blue).is_ok());
+        assert!(task_manager.create_task(task_fields("Title", &["work"]), None).is_ok());
+
+        assert_eq!(task_manager.get_tags()["work"], TagColor::Blue);
+        assert!(matches!(task_manager.set_tag_color("", TagColor::Blue), Err(TaskError::InvalidTagName(_))));
+    }
+
+    #[test]
+    fn delete_tag() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(task_fields("Title", &["work", "bug"]), None).unwrap();
+        assert!(task_manager.set_tag_color("work", TagColor::Cyan).is_ok());
+
+        assert!(task_manager.delete_tag("work").is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), task_fields("Title", &["bug"]));
+        assert!(!task_manager.get_tags().contains_key("work"));
+        assert!(matches!(task_manager.delete_tag("work"), Err(TaskError::TagNotFound(_))));
+
+        //Undoing gives the tag back to the task with the color it had
+        assert_eq!(task_manager.undo().unwrap(), Some(String::from("Delete tag work")));
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), task_fields("Title", &["bug", "work"]));
+        assert_eq!(task_manager.get_tags().get("work"), Some(&TagColor::Cyan));
+        assert!(task_manager.redo().is_ok());
+        assert!(!task_manager.get_tags().contains_key("work"));
+
+        //Tags without tasks can be restored too
+        assert!(task_manager.set_tag_color("home", TagColor::Gray).is_ok());
+        assert!(task_manager.delete_tag("home").is_ok());
+        assert_eq!(task_manager.undo().unwrap(), Some(String::from("Delete tag home")));
+        assert_eq!(task_manager.get_tags().get("home"), Some(&TagColor::Gray));
+    }
+
+    #[test]
+    fn journal_replay_with_tags() {
+        let directory = create_test_directory("journal-replay-with-tags");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = create_journal_task_manager(&file_path);
+        assert!(task_manager.create_task(task_fields("First", &[]), None).is_ok());
+        let task_id = task_manager.create_task(task_fields("Second", &["work"]), None).unwrap();
+        assert!(task_manager.edit_task(task_id, task_fields("Second", &["work", "home"])).is_ok());
+        assert!(task_manager.set_tag_color("home", TagColor::Cyan).is_ok());
+        assert!(task_manager.delete_tag("work").is_ok());
+
+        let reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        assert_eq!(reloaded_task_manager.get_tags(), task_manager.get_tags());
+    }
+
+    fn prioritized_task_fields(title: &str, priority: Option<&str>) -> TaskFields {
+        TaskFields { priority: priority.map(String::from), ..task_fields(title, &[]) }
+    }
+
+    #[test]
+    fn task_priorities() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(prioritized_task_fields("Title", Some("High")), None).unwrap();
+        assert_eq!(task_manager.find_task(task_id).unwrap().priority.as_deref(), Some("High"));
+
+        assert!(task_manager.edit_task(task_id, prioritized_task_fields("Title", None)).is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().priority, None);
+        let edited = task_manager.edit_task(task_id, prioritized_task_fields("Title", Some("Urgent")));
+        assert!(matches!(edited, Err(TaskError::InvalidPriority(priority)) if priority == "Urgent"));
+        let created = task_manager.create_task(prioritized_task_fields("Title", Some("Urgent")), None);
+        assert!(matches!(created, Err(TaskError::InvalidPriority(_))));
+    }
+
+    #[test]
+    fn sort_by_priority() {
+        let mut task_manager = create_task_manager();
+        let mut task_ids = vec![
+            task_manager.create_task(prioritized_task_fields("None", None), None).unwrap(),
+            task_manager.create_task(prioritized_task_fields("Low", Some("Low")), None).unwrap(),
+            task_manager.create_task(prioritized_task_fields("First high", Some("High")), None).unwrap(),
+            task_manager.create_task(prioritized_task_fields("Second high", Some("High")), None).unwrap(),
+        ];
+        //A priority removed from the board counts as no priority
+        task_manager.tasks_state.priorities.retain(|priority| priority.name != "Low");
+
+        task_manager.sort_by_priority(&mut task_ids);
+        assert_eq!(task_ids, vec![TaskId(2), TaskId(3), TaskId(0), TaskId(1)]);
+    }
+
+    fn priority_names(task_manager: &TaskManager) -> Vec<&str> {
+        task_manager.get_priorities().iter().map(|priority| priority.name.as_str()).collect()
+    }
+
+    #[test]
+    fn manage_priorities() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(prioritized_task_fields("Title", Some("High")), None).unwrap();
+
+        assert!(task_manager.add_priority(String::from("Urgent"), TagColor::Magenta).is_ok());
+        assert!(task_manager.move_priority("Urgent", 0).is_ok());
+        assert!(task_manager.set_priority_color("Low", TagColor::Gray).is_ok());
+        assert!(task_manager.rename_priority("High", String::from("Important")).is_ok());
+        assert_eq!(priority_names(&task_manager), vec!["Urgent", "Important", "Medium", "Low"]);
+        assert_eq!(task_manager.find_priority("Low").unwrap().color, TagColor::Gray);
+        assert_eq!(task_manager.find_task(task_id).unwrap().priority.as_deref(), Some("Important"));
+
+        assert!(task_manager.delete_priority("Important").is_ok());
+        assert_eq!(priority_names(&task_manager), vec!["Urgent", "Medium", "Low"]);
+        assert_eq!(task_manager.find_task(task_id).unwrap().priority, None);
+    }
+
+    #[test]
+    fn manage_priorities_errors() {
+        let mut task_manager = create_task_manager();
+        assert!(matches!(task_manager.add_priority(String::new(), TagColor::Red), Err(TaskError::EmptyPriorityName)));
+        assert!(matches!(task_manager.add_priority(String::from("Low"), TagColor::Red), Err(TaskError::PriorityAlreadyExists(_))));
+        assert!(matches!(task_manager.rename_priority("High", String::from("Low")), Err(TaskError::PriorityAlreadyExists(_))));
+        assert!(matches!(task_manager.rename_priority("Urgent", String::from("Top")), Err(TaskError::InvalidPriority(_))));
+        assert!(matches!(task_manager.move_priority("High", 3), Err(TaskError::InvalidPriorityPosition(3))));
+        assert!(matches!(task_manager.delete_priority("Urgent"), Err(TaskError::InvalidPriority(_))));
+        assert_eq!(task_manager.get_priorities(), &priority::default_priorities());
+        assert!(task_manager.undo().unwrap().is_none());
+    }
+
+    #[test]
+    fn undo_priority_changes() {
+        let mut task_manager = create_task_manager();
+        let task_id = task_manager.create_task(prioritized_task_fields("Title", Some("High")), None).unwrap();
+        assert!(task_manager.move_priority("Low", 0).is_ok());
+        assert!(task_manager.delete_priority("High").is_ok());
+
+        assert_eq!(task_manager.undo().unwrap().as_deref(), Some("Delete priority High"));
+        assert_eq!(priority_names(&task_manager), vec!["Low", "High", "Medium"]);
+        assert_eq!(task_manager.find_task(task_id).unwrap().priority.as_deref(), Some("High"));
+        assert!(task_manager.undo().is_ok());
+        assert_eq!(task_manager.get_priorities(), &priority::default_priorities());
+
+        assert!(task_manager.redo().is_ok());
+        assert_eq!(priority_names(&task_manager), vec!["Low", "High", "Medium"]);
+    }
+
+    #[test]
+    fn journal_replay_with_priorities() {
+        let directory = create_test_directory("journal-replay-with-priorities");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = create_journal_task_manager(&file_path);
+        assert!(task_manager.create_task(prioritized_task_fields("Title", Some("Medium")), None).is_ok());
+        assert!(task_manager.add_priority(String::from("Someday"), TagColor::Cyan).is_ok());
+        assert!(task_manager.rename_priority("Medium", String::from("Normal")).is_ok());
+        assert!(task_manager.set_priority_color("High", TagColor::Magenta).is_ok());
+        assert!(task_manager.move_priority("Someday", 1).is_ok());
+        assert!(task_manager.delete_priority("Low").is_ok());
+        assert!(task_manager.undo().is_ok());
+
+        let reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        assert_eq!(reloaded_task_manager.get_priorities(), task_manager.get_priorities());
+    }
+
+    fn dated_task_fields(title: &str, due_date: Option<DateTime<Utc>>, start_date: Option<DateTime<Utc>>) -> TaskFields {
+        TaskFields {
+            due_date: due_date.map(|date| date.fixed_offset()),
+            start_date: start_date.map(|date| date.fixed_offset()),
+            ..task_fields(title, &[])
+        }
+    }
+
+    #[test]
+    fn task_dates() {
+        let mut task_manager = create_task_manager();
+        let now = Utc::now();
+        let task_id = task_manager.create_task(dated_task_fields("Title", Some(now), None), None).unwrap();
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), dated_task_fields("Title", Some(now), None));
+
+        let fields = dated_task_fields("Title", Some(now), Some(now - Duration::days(1)));
+        assert!(task_manager.edit_task(task_id, fields.clone()).is_ok());
+        assert_eq!(task_manager.find_task(task_id).unwrap().fields(), fields);
+
+        let edited = task_manager.edit_task(task_id, dated_task_fields("Title", Some(now), Some(now + Duration::days(1))));
+        assert!(matches!(edited, Err(TaskError::StartAfterDue)));
+        let created = task_manager.create_task(dated_task_fields("Title", Some(now), Some(now + Duration::days(1))), None);
+        assert!(matches!(created, Err(TaskError::StartAfterDue)));
+    }
+
+    #[test]
+    fn due_status() {
+        let mut task_manager = create_task_manager();
+        let now = Utc::now();
+        let overdue = task_manager.create_task(dated_task_fields("Overdue", Some(now - Duration::hours(1)), None), None).unwrap();
+        let due_soon = task_manager.create_task(dated_task_fields("Due soon", Some(now + Duration::hours(1)), None), None).unwrap();
+        let not_due = task_manager.create_task(dated_task_fields("Not due", Some(now + Duration::days(3)), None), None).unwrap();
+        let undated = task_manager.create_task(dated_task_fields("Undated", None, None), None).unwrap();
+
+        let due_status = |task_manager: &TaskManager, task_id| task_manager.get_due_status(task_manager.find_task(task_id).unwrap(), now);
+        assert_eq!(due_status(&task_manager, overdue), DueStatus::Overdue);
+        assert_eq!(due_status(&task_manager, due_soon), DueStatus::DueSoon);
+        assert_eq!(due_status(&task_manager, not_due), DueStatus::NotDue);
+        assert_eq!(due_status(&task_manager, undated), DueStatus::NotDue);
+
+        //Finished tasks are never overdue
+        assert!(task_manager.set_task_state(overdue, String::from("Done")).is_ok());
+        assert_eq!(due_status(&task_manager, overdue), DueStatus::NotDue);
+    }
+
+    #[test]
+    fn deferred_tasks() {
+        let mut task_manager = create_task_manager();
+        let now = Utc::now();
+        let started = task_manager.create_task(dated_task_fields("Started", None, Some(now - Duration::hours(1))), None).unwrap();
+        let deferred = task_manager.create_task(dated_task_fields("Deferred", None, Some(now + Duration::hours(1))), None).unwrap();
+        let undated = task_manager.create_task(dated_task_fields("Undated", None, None), None).unwrap();
+
+        assert!(!task_manager.find_task(started).unwrap().is_deferred(now));
+        assert!(task_manager.find_task(deferred).unwrap().is_deferred(now));
+        assert!(!task_manager.find_task(deferred).unwrap().is_deferred(now + Duration::hours(2)));
+        assert!(!task_manager.find_task(undated).unwrap().is_deferred(now));
+    }
+
+    #[test]
+    fn task_timers() {
+        let mut task_manager = create_task_manager();
+        let first = task_manager.create_task(task_fields("First", &[]), None).unwrap();
+        let second = task_manager.create_task(task_fields("Second", &[]), None).unwrap();
+        let start = Utc::now();
+
+        assert!(task_manager.start_timer(first, start).is_ok());
+        assert!(matches!(task_manager.start_timer(first, start), Err(TaskError::TimerAlreadyRunning(TaskId(0)))));
+        assert_eq!(task_manager.get_running_timer().map(|task| task.id), Some(first));
+        assert_eq!(task_manager.find_task(first).unwrap().time_spent(start + Duration::minutes(5)), Duration::minutes(5));
+
+        //Starting another timer stops the running one
+        assert!(task_manager.start_timer(second, start + Duration::minutes(10)).is_ok());
+        assert_eq!(task_manager.get_running_timer().map(|task| task.id), Some(second));
+        assert_eq!(task_manager.find_task(first).unwrap().time_spent(start + Duration::hours(1)), Duration::minutes(10));
+
+        assert!(task_manager.stop_timer(second, start + Duration::minutes(40)).is_ok());
+        assert!(task_manager.get_running_timer().is_none());
+        assert_eq!(task_manager.find_task(second).unwrap().time_spent(start + Duration::hours(1)), Duration::minutes(30));
+        assert!(matches!(task_manager.stop_timer(second, start), Err(TaskError::TimerNotRunning(TaskId(1)))));
+        assert!(matches!(task_manager.start_timer(TaskId(7), start), Err(TaskError::TaskNotFound(TaskId(7)))));
+
+        assert_eq!(task_manager.undo().unwrap(), Some(String::from("Stop the timer of \"Second\"")));
+        assert_eq!(task_manager.get_running_timer().map(|task| task.id), Some(second));
+    }
+
+    #[test]
+    fn subtree_time_spent() {
+        let mut task_manager = create_task_manager();
+        let parent = task_manager.create_task(task_fields("Parent", &[]), None).unwrap();
+        let child = task_manager.create_task(task_fields("Child", &[]), Some(parent)).unwrap();
+        let grandchild = task_manager.create_task(task_fields("Grandchild", &[]), Some(child)).unwrap();
+        let start = Utc::now();
+        assert!(task_manager.start_timer(parent, start).is_ok());
+        assert!(task_manager.start_timer(grandchild, start + Duration::minutes(15)).is_ok());
+
+        let now = start + Duration::minutes(45);
+        assert_eq!(task_manager.get_subtree_time_spent(parent, now), Duration::minutes(45));
+        assert_eq!(task_manager.get_subtree_time_spent(child, now), Duration::minutes(30));
+        assert_eq!(task_manager.find_task(child).unwrap().time_spent(now), Duration::zero());
+        assert_eq!(task_manager.get_subtree_time_spent(TaskId(7), now), Duration::zero());
+    }
+
+    #[test]
+    fn journal_replay_with_timers() {
+        let directory = create_test_directory("journal-replay-with-timers");
+        let file_path = directory.join("state.json").to_string_lossy().to_string();
+        let mut task_manager = create_journal_task_manager(&file_path);
+        let task_id = task_manager.create_task(task_fields("Title", &[]), None).unwrap();
+        let start = Utc::now();
+        assert!(task_manager.start_timer(task_id, start).is_ok());
+        assert!(task_manager.stop_timer(task_id, start + Duration::minutes(20)).is_ok());
+        assert!(task_manager.start_timer(task_id, start + Duration::hours(1)).is_ok());
+
+        let reloaded_task_manager = create_journal_task_manager(&file_path);
+        assert_eq!(reloaded_task_manager.get_tasks(), task_manager.get_tasks());
+        let report = reloaded_task_manager.get_time_report(start + Duration::hours(2));
+        let total = report.values().flat_map(|tasks| tasks.values()).fold(Duration::zero(), |total, duration| total + *duration);
+        assert_eq!(total, Duration::minutes(80));
     }
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::iter;
+
+use super::{task::TaskId, Task, TaskManager, TimeEntry};
+
+/// A task as shown to other programs, like `tant list --json`.
+///
+/// Its fields are part of the interface of TAnt: unlike the stored [`Task`], they are only ever added to,
+/// never renamed or removed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskView {
+    pub id: u64,
+    pub title: String,
+    pub state: String,
+    pub description: String,
+    pub parent: Option<u64>,
+    pub children: Vec<u64>,
+    //Number of ancestors, 0 for top level tasks
+    pub depth: usize,
+    //In alphabetical order
+    pub tags: Vec<String>,
+    pub priority: Option<String>,
+    //RFC 3339, in the time zone they were given in
+    pub due_date: Option<DateTime<FixedOffset>>,
+    pub start_date: Option<DateTime<FixedOffset>>,
+    //The last one has no end while the timer is running
+    pub time_entries: Vec<TimeEntry>,
+}
+
+impl TaskView {
+    pub fn new(task: &Task, depth: usize) -> TaskView {
+        TaskView {
+            id: task.id.0,
+            title: task.title.clone(),
+            state: task.state.clone(),
+            description: task.description.clone(),
+            parent: task.parent_task.map(|parent| parent.0),
+            children: task.child_tasks.iter().map(|child| child.0).collect(),
+            depth,
+            tags: task.tags.iter().cloned().collect(),
+            priority: task.priority.clone(),
+            due_date: task.due_date,
+            start_date: task.start_date,
+            time_entries: task.time_entries.clone(),
+        }
+    }
+
+    /// Every task, each one followed by its subtasks.
+    pub fn list(task_manager: &TaskManager) -> Vec<TaskView> {
+        task_manager.get_task_tree().into_iter().map(|(task, depth)| TaskView::new(task, depth)).collect()
+    }
+
+    pub fn find(task_manager: &TaskManager, task_id: TaskId) -> Option<TaskView> {
+        let task = task_manager.find_task(task_id)?;
+        let ancestors = iter::successors(task.parent_task, |parent| task_manager.find_task(*parent)?.parent_task);
+        Some(TaskView::new(task, ancestors.count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::task::TaskId;
+    use serde_json::json;
+
+    #[test]
+    fn task_view_schema() {
+        let task = Task {
+            id: TaskId(2),
+            title: String::from("Title"),
+            state: String::from("Open"),
+            description: String::from("Description"),
+            parent_task: Some(TaskId(1)),
+            child_tasks: vec![TaskId(3), TaskId(4)],
+            tags: [String::from("work"), String::from("bug")].into(),
+            priority: Some(String::from("High")),
+            due_date: DateTime::parse_from_rfc3339("2024-05-01T18:00:00+02:00").ok(),
+            start_date: None,
+            time_entries: vec![TimeEntry {
+                start: DateTime::parse_from_rfc3339("2024-04-30T08:00:00Z").unwrap().to_utc(),
+                end: None,
+            }],
+        };
+        let expected_view = json!({
+            "id": 2,
+            "title": "Title",
+            "state": "Open",
+            "description": "Description",
+            "parent": 1,
+            "children": [3, 4],
+            "depth": 1,
+            "tags": ["bug", "work"],
+            "priority": "High",
+            "due_date": "2024-05-01T18:00:00+02:00",
+            "start_date": null,
+            "time_entries": [{ "start": "2024-04-30T08:00:00Z", "end": null }]
+        });
+        assert_eq!(serde_json::to_value(TaskView::new(&task, 1)).unwrap(), expected_view);
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::{history::History, migrations, priority, task::TaskId, Priority, TagColor, Task, TaskError, Workflow};
+
+/// Everything stored about a board, in the format of the current version.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TasksState {
+    pub(crate) version: u64,
+    pub(crate) tasks: BTreeMap<TaskId, Task>,
+    pub(crate) valid_states: Vec<String>,
+    pub(crate) next_valid_id: u64,
+    #[serde(default)]
+    pub(crate) workflow: Workflow,
+    #[serde(default)]
+    pub(crate) history: History,
+    //Last journal event included in this state
+    #[serde(default)]
+    pub(crate) journal_sequence: u64,
+    //Every tag used by the tasks, with its color
+    #[serde(default)]
+    pub(crate) tags: BTreeMap<String, TagColor>,
+    #[serde(default = "priority::default_priorities")]
+    pub(crate) priorities: Vec<Priority>,
+}
+
+impl Default for TasksState {
+    fn default() -> TasksState {
+        TasksState {
+            version: migrations::CURRENT_VERSION,
+            tasks: BTreeMap::new(),
+            valid_states: Vec::new(),
+            next_valid_id: 0,
+            workflow: Workflow::default(),
+            history: History::default(),
+            journal_sequence: 0,
+            tags: BTreeMap::new(),
+            priorities: priority::default_priorities(),
+        }
+    }
+}
+
+impl TasksState {
+    /// Reads a document of any supported version, migrating it to the current one.
+    pub fn from_document(document: Value) -> Result<TasksState, TaskError> {
+        let document = migrations::migrate(document)?;
+        Ok(serde_json::from_value(document)?)
+    }
+
+    pub fn to_document(&self) -> Result<Value, TaskError> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    /// Sequence of the last change included in this state.
+    pub fn journal_sequence(&self) -> u64 {
+        self.journal_sequence
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{task::TaskId, Task};
+
+/// Time spent on a task. The timer of the task is running while the entry has no end.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeEntry {
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Time between the start and the end of the entry, or `now` if it is still running.
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::zero())
+    }
+
+    /// The duration of the entry on each day it spans, as days are in `time_zone`.
+    pub fn split_by_day<Tz: TimeZone>(&self, now: DateTime<Utc>, time_zone: &Tz) -> Vec<(NaiveDate, Duration)> {
+        let end = self.end.unwrap_or(now);
+        let mut days = Vec::new();
+        let mut day_start = self.start;
+        while day_start < end {
+            let day = day_start.with_timezone(time_zone).date_naive();
+            //A midnight skipped by a daylight saving change ends the day at the end of the entry
+            let next_day_start = day
+                .succ_opt()
+                .and_then(|next_day| time_zone.from_local_datetime(&next_day.and_time(NaiveTime::MIN)).earliest())
+                .map_or(end, |next_day_start| next_day_start.with_timezone(&Utc).min(end));
+            days.push((day, next_day_start - day_start));
+            day_start = next_day_start;
+        }
+        days
+    }
+}
+
+/// Time spent on each task, grouped by the day it was spent in `time_zone`.
+pub fn time_report<'a, Tz: TimeZone>(
+    tasks: impl Iterator<Item = &'a Task>,
+    now: DateTime<Utc>,
+    time_zone: &Tz,
+) -> BTreeMap<NaiveDate, BTreeMap<TaskId, Duration>> {
+    let mut report: BTreeMap<NaiveDate, BTreeMap<TaskId, Duration>> = BTreeMap::new();
+    for task in tasks {
+        for (day, duration) in task.time_entries.iter().flat_map(|entry| entry.split_by_day(now, time_zone)) {
+            let task_duration = report.entry(day).or_default().entry(task.id).or_insert_with(Duration::zero);
+            *task_duration += duration;
+        }
+    }
+    report
+}
+
+/// A duration in hours and minutes, like `2h 05m`.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    match minutes / 60 {
+        0 => format!("{}m", minutes),
+        hours => format!("{}h {:02}m", hours, minutes % 60),
+    }
+}
+
+/// A duration as shown by a running timer, like `2:05:09`.
+pub fn format_timer(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn time(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn entry_durations() {
+        let entry = TimeEntry { start: time("2024-03-01T10:00:00Z"), end: Some(time("2024-03-01T11:30:00Z")) };
+        assert_eq!(entry.duration(time("2024-03-02T00:00:00Z")), Duration::minutes(90));
+        assert!(!entry.is_running());
+
+        let running_entry = TimeEntry { start: time("2024-03-01T10:00:00Z"), end: None };
+        assert_eq!(running_entry.duration(time("2024-03-01T10:20:00Z")), Duration::minutes(20));
+        assert!(running_entry.is_running());
+    }
+
+    #[test]
+    fn split_entries_by_day() {
+        let time_zone = FixedOffset::east_opt(2 * 3600).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+
+        //21:00 to 03:00 in UTC+2
+        let entry = TimeEntry { start: time("2024-03-01T19:00:00Z"), end: Some(time("2024-03-02T01:00:00Z")) };
+        assert_eq!(entry.split_by_day(Utc::now(), &time_zone), vec![(day(1), Duration::hours(3)), (day(2), Duration::hours(3))]);
+        assert_eq!(entry.split_by_day(Utc::now(), &Utc), vec![(day(1), Duration::hours(5)), (day(2), Duration::hours(1))]);
+
+        let running_entry = TimeEntry { start: time("2024-03-01T19:00:00Z"), end: None };
+        let now = time("2024-03-01T20:00:00Z");
+        assert_eq!(running_entry.split_by_day(now, &time_zone), vec![(day(1), Duration::hours(1))]);
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(Duration::seconds(59)), "0m");
+        assert_eq!(format_duration(Duration::minutes(42)), "42m");
+        assert_eq!(format_duration(Duration::minutes(125)), "2h 05m");
+        assert_eq!(format_timer(Duration::seconds(7509)), "2:05:09");
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub requires_final_children: bool,
+}
+
+/// Rules describing how tasks may move between states.
+///
+/// A workflow without transitions does not restrict anything, so every state can be reached from
+/// every other one. As soon as a transition is defined only the listed ones are allowed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Workflow {
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+    #[serde(default)]
+    pub final_states: Vec<String>,
+}
+
+impl Workflow {
+    pub fn find_transition(&self, from: &str, to: &str) -> Option<&Transition> {
+        self.transitions
+            .iter()
+            .find(|transition| transition.from == from && transition.to == to)
+    }
+
+    pub fn is_transition_allowed(&self, from: &str, to: &str) -> bool {
+        from == to || self.transitions.is_empty() || self.find_transition(from, to).is_some()
+    }
+
+    pub fn requires_final_children(&self, from: &str, to: &str) -> bool {
+        self.find_transition(from, to)
+            .is_some_and(|transition| transition.requires_final_children)
+    }
+
+    pub fn is_final_state(&self, state: &str) -> bool {
+        self.final_states.iter().any(|final_state| final_state == state)
+    }
+}
@@ -0,0 +1,676 @@
+use std::{
+    env,
+    error::Error,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::backend::{
+    backups, dates, journal, storage::JsonFileStorage, task::TaskId, time_tracking, DeleteMode, DueStatus, TagColor, Task,
+    TaskFields, TaskManager, TaskView,
+};
+use crate::config::{Config, ConfigError, StorageKind, CONFIG_ENV_VAR, FILE_ENV_VAR};
+use crate::server;
+use crate::workspace::{Workspace, WORKSPACE_DIRECTORY_NAME};
+
+#[derive(Parser)]
+#[command(version, about = "Terminal application for managing your tasks")]
+pub struct Cli {
+    /// Configuration file, by default tant.toml in the workspace
+    #[arg(long, env = CONFIG_ENV_VAR)]
+    pub config: Option<PathBuf>,
+
+    /// File or directory where the tasks are stored, by default in the workspace
+    #[arg(long, env = FILE_ENV_VAR)]
+    pub file: Option<String>,
+
+    /// Where the tasks are stored, overrides the storage of tant.toml
+    #[arg(long, value_enum)]
+    pub storage: Option<StorageKind>,
+
+    /// Number of previous versions of the tasks file to keep
+    #[arg(long)]
+    pub backups: Option<usize>,
+
+    /// Append every change to a journal instead of rewriting the whole tasks file
+    #[arg(long)]
+    pub journal: bool,
+
+    /// Use the global board even inside a workspace
+    #[arg(long)]
+    pub global: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a workspace with its own board in the current directory
+    Init,
+    /// Add a task and print its id
+    Add {
+        title: String,
+        /// State of the task, the first state by default
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long, default_value = "")]
+        description: String,
+        /// Id of the parent task
+        #[arg(long)]
+        parent: Option<u64>,
+        /// Tag of the task, can be given several times
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Priority of the task, one of the priorities of the board
+        #[arg(long)]
+        priority: Option<String>,
+        /// Due date: YYYY-MM-DD, YYYY-MM-DD HH:MM, today, tomorrow, +3d, +2w or an RFC 3339 date
+        #[arg(long, value_parser = parse_due_date)]
+        due: Option<DateTime<FixedOffset>>,
+        /// Date the task is hidden until, in the same formats as --due
+        #[arg(long, value_parser = parse_start_date)]
+        start: Option<DateTime<FixedOffset>>,
+    },
+    /// List the tasks, each one followed by its subtasks
+    List {
+        /// Only list the tasks in this state
+        #[arg(long)]
+        state: Option<String>,
+        /// Only list the tasks with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Leave out the tasks whose start date has not come yet, along with their subtasks
+        #[arg(long)]
+        hide_deferred: bool,
+        /// Print a JSON array of tasks
+        #[arg(long, conflicts_with = "ndjson")]
+        json: bool,
+        /// Print a JSON task per line
+        #[arg(long)]
+        ndjson: bool,
+    },
+    /// Show every field of a task
+    Show {
+        id: u64,
+        /// Print the task as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change the state of a task
+    Move { id: u64, state: String },
+    /// Change the fields of a task, the ones not given are kept
+    Edit {
+        id: u64,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Id of the new parent task
+        #[arg(long, conflicts_with = "no_parent")]
+        parent: Option<u64>,
+        /// Make it a top level task
+        #[arg(long)]
+        no_parent: bool,
+        /// Tag to add to the task, can be given several times
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Tag to remove from the task, can be given several times
+        #[arg(long = "untag")]
+        removed_tags: Vec<String>,
+        #[arg(long, conflicts_with = "no_priority")]
+        priority: Option<String>,
+        /// Remove the priority of the task
+        #[arg(long)]
+        no_priority: bool,
+        #[arg(long, conflicts_with = "no_due", value_parser = parse_due_date)]
+        due: Option<DateTime<FixedOffset>>,
+        /// Remove the due date of the task
+        #[arg(long)]
+        no_due: bool,
+        #[arg(long, conflicts_with = "no_start", value_parser = parse_start_date)]
+        start: Option<DateTime<FixedOffset>>,
+        /// Remove the start date of the task
+        #[arg(long)]
+        no_start: bool,
+    },
+    /// Delete a task
+    Delete {
+        id: u64,
+        /// What to do with the subtasks, required when the task has any
+        #[arg(long, value_enum)]
+        subtasks: Option<SubtasksAction>,
+    },
+    /// List the tags with their colors and number of tasks
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+    /// List the priorities tasks can have, from the highest to the lowest
+    Priorities {
+        #[command(subcommand)]
+        command: Option<PrioritiesCommand>,
+    },
+    /// Show the running timer
+    Timer {
+        #[command(subcommand)]
+        command: Option<TimerCommand>,
+    },
+    /// Print the time spent on each task, grouped by day
+    Report {
+        /// First day of the report, in the same formats as --due of add
+        #[arg(long, value_parser = parse_report_day)]
+        from: Option<NaiveDate>,
+        /// Last day of the report, in the same formats as --due of add
+        #[arg(long, value_parser = parse_report_day)]
+        to: Option<NaiveDate>,
+        /// Print a JSON array with the time of each task and day
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the backups of the tasks file, most recent first
+    Backups,
+    /// Replace the tasks with one of the backups
+    Restore {
+        /// Index of the backup, as shown by `tant backups`
+        backup: usize,
+    },
+    /// Serve an HTTP JSON API to read and change the tasks
+    Serve {
+        /// Address to listen on, only reachable from this machine by default
+        #[arg(long, default_value = "127.0.0.1:7171")]
+        listen: SocketAddr,
+    },
+    /// Inspect or compact the journal of changes
+    Journal {
+        #[command(subcommand)]
+        command: JournalCommand,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SubtasksAction {
+    /// Delete them too
+    Delete,
+    /// Move them to the parent of the deleted task
+    Reparent,
+}
+
+#[derive(Subcommand)]
+pub enum TagsCommand {
+    /// Change the color of a tag: red, green, yellow, blue, magenta, cyan or gray
+    Color { tag: String, color: TagColor },
+    /// Remove a tag from every task
+    Delete { tag: String },
+}
+
+#[derive(Subcommand)]
+pub enum PrioritiesCommand {
+    /// Add a priority below the lowest one, colored like tags
+    Add { name: String, color: TagColor },
+    /// Rename a priority, also in the tasks that have it
+    Rename { name: String, new_name: String },
+    /// Change the color of a priority
+    Color { name: String, color: TagColor },
+    /// Move a priority to another position, 1 being the highest
+    Move { name: String, position: usize },
+    /// Remove a priority, the tasks that have it are left without one
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum TimerCommand {
+    /// Start tracking the time spent on a task, stopping the running timer
+    Start { id: u64 },
+    /// Stop the running timer
+    Stop,
+}
+
+#[derive(Subcommand)]
+pub enum JournalCommand {
+    /// List the changes in the journal, oldest first
+    List,
+    /// Write the whole tasks file and remove the changes it includes from the journal
+    Compact,
+}
+
+impl Cli {
+    /// The workspace found from `directory`, unless the global board was requested.
+    pub fn workspace(&self, directory: &Path) -> Workspace {
+        if self.global {
+            return Workspace::Global;
+        }
+        Workspace::discover(directory)
+    }
+
+    /// Reads the configuration given with `--config`, which has to exist, or the one of the workspace if any.
+    pub fn load_config(&self, workspace: &Workspace) -> Result<Config, ConfigError> {
+        match &self.config {
+            Some(config_path) => Config::load_required(config_path),
+            None => Config::load(&workspace.config_path()),
+        }
+    }
+
+    /// Overrides the configuration with the options given in the command line.
+    pub fn apply_to(&self, config: &mut Config) {
+        if let Some(storage) = self.storage {
+            config.storage = storage;
+        }
+        if let Some(backups) = self.backups {
+            config.backups = backups;
+        }
+        config.journal |= self.journal;
+        if let Some(file) = &self.file {
+            config.file = Some(file.clone());
+        }
+    }
+}
+
+fn init_workspace(directory: &Path) -> Result<(), Box<dyn Error>> {
+    let workspace_directory = directory.join(WORKSPACE_DIRECTORY_NAME);
+    if workspace_directory.exists() {
+        return Err(format!("{} already exists", workspace_directory.display()).into());
+    }
+    fs::create_dir(&workspace_directory)?;
+    println!("Created an empty board in {}", workspace_directory.display());
+    Ok(())
+}
+
+pub fn run_command(command: Command, config: &Config, task_manager: &mut TaskManager) -> Result<(), Box<dyn Error>> {
+    let file_path = &config.tasks_file_path();
+    match command {
+        Command::Init => init_workspace(&env::current_dir()?)?,
+        Command::Add { title, state, description, parent, tags, priority, due, start } => {
+            task_manager.initialize()?;
+            let state = match state {
+                Some(state) => state,
+                None => task_manager.get_states().first().cloned().ok_or("There are no states")?,
+            };
+            let fields = TaskFields {
+                title,
+                state,
+                description,
+                tags: tags.into_iter().collect(),
+                priority,
+                due_date: due,
+                start_date: start,
+            };
+            let task_id = task_manager.create_task(fields, parent.map(TaskId))?;
+            println!("{}", task_id.0);
+        }
+        Command::List { state, tag, hide_deferred, json, ndjson } => {
+            task_manager.initialize()?;
+            let now = Utc::now();
+            let task_tree: Vec<(&Task, usize)> = task_manager
+                .get_task_tree()
+                .into_iter()
+                .filter(|(task, _)| state.as_ref().is_none_or(|state| *state == task.state))
+                .filter(|(task, _)| tag.as_ref().is_none_or(|tag| task.tags.contains(tag)))
+                .filter(|(task, _)| !hide_deferred || !is_deferred_with_ancestors(task_manager, task, now))
+                .collect();
+            if json {
+                let task_views: Vec<TaskView> = task_tree.iter().map(|(task, depth)| TaskView::new(task, *depth)).collect();
+                println!("{}", serde_json::to_string_pretty(&task_views)?);
+            }
+            else if ndjson {
+                for (task, depth) in task_tree {
+                    println!("{}", serde_json::to_string(&TaskView::new(task, depth))?);
+                }
+            }
+            else {
+                for (task, depth) in task_tree {
+                    println!(
+                        "{:>4}  {}{} [{}]{}{}{}",
+                        task.id.0,
+                        "    ".repeat(depth),
+                        task.title,
+                        task.state,
+                        describe_priority(task),
+                        describe_tags(task),
+                        describe_due_date(task_manager, task)
+                    );
+                }
+            }
+        }
+        Command::Show { id, json } => {
+            task_manager.initialize()?;
+            let task = find_task(task_manager, id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&TaskView::find(task_manager, task.id))?);
+                return Ok(());
+            }
+            println!("Id:          {}", task.id.0);
+            println!("Title:       {}", task.title);
+            println!("State:       {}", task.state);
+            if let Some(priority) = &task.priority {
+                println!("Priority:    {}", priority);
+            }
+            if let Some(due_date) = &task.due_date {
+                println!("Due:         {}{}", dates::format_date(due_date), describe_due_status(task_manager, task));
+            }
+            if let Some(start_date) = &task.start_date {
+                println!("Start:       {}", dates::format_date(start_date));
+            }
+            if !task.tags.is_empty() {
+                println!("Tags:        {}", describe_tags(task).trim_start());
+            }
+            if !task.time_entries.is_empty() || !task.child_tasks.is_empty() {
+                let now = Utc::now();
+                let running = if task.running_entry().is_some() { ", running" } else { "" };
+                println!(
+                    "Time spent:  {}{} ({} with subtasks)",
+                    time_tracking::format_duration(task.time_spent(now)),
+                    running,
+                    time_tracking::format_duration(task_manager.get_subtree_time_spent(task.id, now))
+                );
+            }
+            if let Some(parent_task) = task.parent_task.and_then(|parent| task_manager.find_task(parent)) {
+                println!("Parent:      {}", describe_task(parent_task));
+            }
+            for child_task in task.child_tasks.iter().filter_map(|child| task_manager.find_task(*child)) {
+                println!("Subtask:     {}", describe_task(child_task));
+            }
+            println!("Description: {}", task.description);
+        }
+        Command::Move { id, state } => {
+            task_manager.initialize()?;
+            task_manager.set_task_state(TaskId(id), state)?;
+        }
+        Command::Edit {
+            id,
+            title,
+            state,
+            description,
+            parent,
+            no_parent,
+            tags,
+            removed_tags,
+            priority,
+            no_priority,
+            due,
+            no_due,
+            start,
+            no_start,
+        } => {
+            task_manager.initialize()?;
+            let task = find_task(task_manager, id)?.clone();
+            let mut fields = task.fields();
+            fields.title = title.unwrap_or(fields.title);
+            fields.state = state.unwrap_or(fields.state);
+            fields.description = description.unwrap_or(fields.description);
+            fields.tags.extend(tags);
+            fields.tags.retain(|tag| !removed_tags.contains(tag));
+            if priority.is_some() || no_priority {
+                fields.priority = priority;
+            }
+            if due.is_some() || no_due {
+                fields.due_date = due;
+            }
+            if start.is_some() || no_start {
+                fields.start_date = start;
+            }
+            //The move is checked first so a failed command does not change anything
+            let new_parent = (parent.is_some() || no_parent).then(|| parent.map(TaskId));
+            if let Some(new_parent) = new_parent {
+                task_manager.check_move(task.id, new_parent)?;
+            }
+            if fields != task.fields() {
+                task_manager.edit_task(task.id, fields)?;
+            }
+            if let Some(new_parent) = new_parent {
+                task_manager.move_task(task.id, new_parent)?;
+            }
+        }
+        Command::Delete { id, subtasks } => {
+            task_manager.initialize()?;
+            let task = find_task(task_manager, id)?;
+            let delete_mode = match subtasks {
+                Some(SubtasksAction::Delete) => DeleteMode::DeleteChildren,
+                Some(SubtasksAction::Reparent) => DeleteMode::ReparentChildren,
+                None if task.child_tasks.is_empty() => DeleteMode::DeleteChildren,
+                None => return Err(format!("Task {} has subtasks, choose what to do with them with --subtasks", id).into()),
+            };
+            task_manager.delete_task(TaskId(id), delete_mode)?;
+        }
+        Command::Tags { command: None } => {
+            task_manager.initialize()?;
+            for (tag, color) in task_manager.get_tags() {
+                let task_count = task_manager.get_tasks().iter().filter(|task| task.tags.contains(tag)).count();
+                println!("{:<20} {:<8} {} tasks", tag, color, task_count);
+            }
+        }
+        Command::Tags { command: Some(TagsCommand::Color { tag, color }) } => {
+            task_manager.initialize()?;
+            task_manager.set_tag_color(&tag, color)?;
+        }
+        Command::Tags { command: Some(TagsCommand::Delete { tag }) } => {
+            task_manager.initialize()?;
+            task_manager.delete_tag(&tag)?;
+        }
+        Command::Priorities { command: None } => {
+            task_manager.initialize()?;
+            for priority in task_manager.get_priorities() {
+                let task_count = task_manager
+                    .get_tasks()
+                    .iter()
+                    .filter(|task| task.priority.as_ref() == Some(&priority.name))
+                    .count();
+                println!("{:<20} {:<8} {} tasks", priority.name, priority.color, task_count);
+            }
+        }
+        Command::Priorities { command: Some(PrioritiesCommand::Add { name, color }) } => {
+            task_manager.initialize()?;
+            task_manager.add_priority(name, color)?;
+        }
+        Command::Priorities { command: Some(PrioritiesCommand::Rename { name, new_name }) } => {
+            task_manager.initialize()?;
+            task_manager.rename_priority(&name, new_name)?;
+        }
+        Command::Priorities { command: Some(PrioritiesCommand::Color { name, color }) } => {
+            task_manager.initialize()?;
+            task_manager.set_priority_color(&name, color)?;
+        }
+        Command::Priorities { command: Some(PrioritiesCommand::Move { name, position }) } => {
+            task_manager.initialize()?;
+            if position == 0 || position > task_manager.get_priorities().len() {
+                return Err(format!("There is no priority position {}", position).into());
+            }
+            task_manager.move_priority(&name, position - 1)?;
+        }
+        Command::Priorities { command: Some(PrioritiesCommand::Delete { name }) } => {
+            task_manager.initialize()?;
+            task_manager.delete_priority(&name)?;
+        }
+        Command::Timer { command: None } => {
+            task_manager.initialize()?;
+            match task_manager.get_running_timer() {
+                Some(task) => println!("{}  running for {}", describe_task(task), describe_running_time(task)),
+                None => println!("No timer is running"),
+            }
+        }
+        Command::Timer { command: Some(TimerCommand::Start { id }) } => {
+            task_manager.initialize()?;
+            let task_id = find_task(task_manager, id)?.id;
+            task_manager.start_timer(task_id, Utc::now())?;
+        }
+        Command::Timer { command: Some(TimerCommand::Stop) } => {
+            task_manager.initialize()?;
+            let task = task_manager.get_running_timer().ok_or("No timer is running")?;
+            println!("{}  stopped after {}", describe_task(task), describe_running_time(task));
+            task_manager.stop_timer(task.id, Utc::now())?;
+        }
+        Command::Report { from, to, json } => {
+            task_manager.initialize()?;
+            let report = task_manager.get_time_report(Utc::now());
+            //Reports only include existing tasks
+            let title = |task_id: &TaskId| task_manager.find_task(*task_id).map_or("", |task| task.title.as_str());
+            let days = report
+                .iter()
+                .filter(|(day, _)| from.is_none_or(|from| **day >= from) && to.is_none_or(|to| **day <= to));
+            if json {
+                let rows: Vec<serde_json::Value> = days
+                    .flat_map(|(day, task_times)| task_times.iter().map(move |(task_id, duration)| (day, task_id, duration)))
+                    .map(|(day, task_id, duration)| {
+                        serde_json::json!({
+                            "day": day,
+                            "id": task_id.0,
+                            "title": title(task_id),
+                            "seconds": duration.num_seconds(),
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+                return Ok(());
+            }
+            let mut total = Duration::zero();
+            for (day, task_times) in days {
+                let day_total = task_times.values().fold(Duration::zero(), |day_total, duration| day_total + *duration);
+                total += day_total;
+                println!("{:<50} {:>8}", day.to_string(), time_tracking::format_duration(day_total));
+                for (task_id, duration) in task_times {
+                    println!("{:>6}  {:<42} {:>8}", task_id.0, title(task_id), time_tracking::format_duration(*duration));
+                }
+            }
+            println!("{:<50} {:>8}", "Total", time_tracking::format_duration(total));
+        }
+        Command::Backups => {
+            check_json_storage(config)?;
+            let backup_indices = backups::list_backups(file_path);
+            if backup_indices.is_empty() {
+                println!("There are no backups of {}", file_path);
+            }
+            for index in backup_indices {
+                let backup_path = backups::backup_path(file_path, index);
+                println!("{}  {}  {}", index, backup_path, describe_age(&backup_path));
+            }
+        }
+        Command::Restore { backup } => {
+            check_json_storage(config)?;
+            let backup_path = backups::backup_path(file_path, backup);
+            if !Path::new(&backup_path).exists() {
+                return Err(format!("{} does not exist", backup_path).into());
+            }
+            let mut backup_storage = JsonFileStorage::new(backup_path.clone());
+            backup_storage.set_backup_count(0);
+            task_manager.restore_from(&mut backup_storage)?;
+            println!("Restored {}", backup_path);
+        }
+        Command::Serve { listen } => {
+            task_manager.initialize()?;
+            server::serve(listen, task_manager)?;
+        }
+        Command::Journal { command: JournalCommand::List } => {
+            check_json_storage(config)?;
+            let journal = journal::read(&journal::journal_path(file_path))?;
+            for (_, entry) in journal.entries {
+                let time = entry.time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S");
+                println!("{:>6}  {}  {}", entry.sequence, time, entry.event);
+            }
+            for (line, error) in journal.invalid_lines {
+                eprintln!("Line {} could not be read: {}", line, error);
+            }
+            if journal.partial_last_line {
+                eprintln!("The last line is still being written or was cut by a crash, it is skipped");
+            }
+        }
+        Command::Journal { command: JournalCommand::Compact } => {
+            check_json_storage(config)?;
+            task_manager.initialize()?;
+            let removed_entries = task_manager.compact_journal()?;
+            println!("Removed {} changes from the journal", removed_entries);
+        }
+    }
+    Ok(())
+}
+
+fn find_task(task_manager: &TaskManager, id: u64) -> Result<&Task, Box<dyn Error>> {
+    task_manager
+        .find_task(TaskId(id))
+        .ok_or_else(|| format!("There is no task with id {}", id).into())
+}
+
+fn describe_task(task: &Task) -> String {
+    format!("{} {} [{}]{}{}", task.id.0, task.title, task.state, describe_priority(task), describe_tags(task))
+}
+
+fn describe_running_time(task: &Task) -> String {
+    let running_time = task.running_entry().map_or(Duration::zero(), |entry| entry.duration(Utc::now()));
+    time_tracking::format_timer(running_time)
+}
+
+fn describe_due_date(task_manager: &TaskManager, task: &Task) -> String {
+    match &task.due_date {
+        Some(due_date) => format!(" due {}{}", dates::format_date(due_date), describe_due_status(task_manager, task)),
+        None => String::new(),
+    }
+}
+
+fn describe_due_status(task_manager: &TaskManager, task: &Task) -> &'static str {
+    match task_manager.get_due_status(task, Utc::now()) {
+        DueStatus::Overdue => " (overdue)",
+        DueStatus::DueSoon => " (due soon)",
+        DueStatus::NotDue => "",
+    }
+}
+
+//Subtasks of a deferred task are hidden with it
+fn is_deferred_with_ancestors(task_manager: &TaskManager, task: &Task, now: DateTime<Utc>) -> bool {
+    task.is_deferred(now)
+        || task
+            .parent_task
+            .and_then(|parent| task_manager.find_task(parent))
+            .is_some_and(|parent| is_deferred_with_ancestors(task_manager, parent, now))
+}
+
+fn parse_due_date(text: &str) -> Result<DateTime<FixedOffset>, String> {
+    dates::parse_date(text, dates::end_of_day(), Local::now().date_naive())
+}
+
+fn parse_report_day(text: &str) -> Result<NaiveDate, String> {
+    let date = dates::parse_date(text, dates::start_of_day(), Local::now().date_naive())?;
+    Ok(date.with_timezone(&Local).date_naive())
+}
+
+fn parse_start_date(text: &str) -> Result<DateTime<FixedOffset>, String> {
+    dates::parse_date(text, dates::start_of_day(), Local::now().date_naive())
+}
+
+fn describe_priority(task: &Task) -> String {
+    task.priority.as_ref().map(|priority| format!(" !{}", priority)).unwrap_or_default()
+}
+
+fn describe_tags(task: &Task) -> String {
+    task.tags.iter().map(|tag| format!(" #{}", tag)).collect()
+}
+
+//Backups and the journal are only kept by the json storage
+fn check_json_storage(config: &Config) -> Result<(), Box<dyn Error>> {
+    if config.storage != StorageKind::Json {
+        return Err("Backups and the journal are only available with the json storage".into());
+    }
+    Ok(())
+}
+
+fn describe_age(file_path: &str) -> String {
+    let elapsed = fs::metadata(file_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    let seconds = match elapsed {
+        Some(elapsed) => elapsed.as_secs(),
+        None => return String::new(),
+    };
+    match seconds {
+        0..=59 => format!("{} seconds ago", seconds),
+        60..=3599 => format!("{} minutes ago", seconds / 60),
+        3600..=86399 => format!("{} hours ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
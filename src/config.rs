@@ -0,0 +1,175 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::backend::{
+    backups,
+    storage::{DirectoryStorage, JsonFileStorage, MemoryStorage, SqliteStorage},
+    Storage, TaskError,
+};
+use crate::hooks::HooksConfig;
+use crate::workspace::Workspace;
+
+pub const CONFIG_FILE_NAME: &str = "tant.toml";
+pub const CONFIG_ENV_VAR: &str = "TANT_CONFIG";
+pub const FILE_ENV_VAR: &str = "TANT_FILE";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// A JSON file, state.json
+    #[default]
+    Json,
+    /// An SQLite database, state.db
+    Sqlite,
+    /// A directory with a file per task, state/
+    Directory,
+    /// Nothing is stored, the tasks are lost on exit
+    Memory,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub storage: StorageKind,
+    //Only used by the json storage
+    pub backups: usize,
+    pub journal: bool,
+    //Where the tasks are stored, a file or a directory depending on the storage
+    pub file: Option<String>,
+    pub hooks: HooksConfig,
+    //Where the tasks are stored when no file is given, set by the workspace
+    #[serde(skip)]
+    pub data_directory: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            storage: StorageKind::default(),
+            backups: backups::DEFAULT_BACKUP_COUNT,
+            journal: false,
+            file: None,
+            hooks: HooksConfig::default(),
+            data_directory: Workspace::Global.data_directory(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    //A configuration file given explicitly that does not exist
+    NotFound(PathBuf),
+    Io(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NotFound(path) => write!(f, "The configuration file {} does not exist", path.display()),
+            ConfigError::Io(error) => write!(f, "Could not read the configuration: {}", error),
+            ConfigError::Parse(error) => write!(f, "Invalid configuration: {}", error),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration file, using the default configuration when it does not exist.
+    /// A relative `file` or hook is relative to the directory of the configuration file.
+    pub fn load(file_path: &Path) -> Result<Config, ConfigError> {
+        Config::read(file_path, false)
+    }
+
+    /// Reads a configuration file given explicitly, like `load` but failing when it does not exist.
+    pub fn load_required(file_path: &Path) -> Result<Config, ConfigError> {
+        Config::read(file_path, true)
+    }
+
+    fn read(file_path: &Path, required: bool) -> Result<Config, ConfigError> {
+        let mut config: Config = match fs::read_to_string(file_path) {
+            Ok(content) => toml::from_str(&content).map_err(ConfigError::Parse)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound && required => {
+                return Err(ConfigError::NotFound(file_path.to_path_buf()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => return Err(ConfigError::Io(error)),
+        };
+        let config_directory = file_path.parent().unwrap_or(Path::new(""));
+        config.file = config.file.map(|file| config_directory.join(file).to_string_lossy().to_string());
+        config.hooks.resolve_paths(config_directory);
+        Ok(config)
+    }
+
+    /// The file given in the configuration or, by default, a file in the data directory of the workspace.
+    pub fn tasks_file_path(&self) -> String {
+        let default_file_name = match self.storage {
+            StorageKind::Json => "state.json",
+            StorageKind::Sqlite => "state.db",
+            StorageKind::Directory => "state",
+            //Nothing is written
+            StorageKind::Memory => return String::new(),
+        };
+        match &self.file {
+            Some(file) => file.clone(),
+            None => self.data_directory.join(default_file_name).to_string_lossy().to_string(),
+        }
+    }
+
+    pub fn create_storage(&self) -> Result<Box<dyn Storage>, TaskError> {
+        let file_path = self.tasks_file_path();
+        if let Some(directory) = Path::new(&file_path).parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)?;
+        }
+        let storage: Box<dyn Storage> = match self.storage {
+            StorageKind::Json => {
+                let mut storage = JsonFileStorage::new(file_path);
+                storage.set_backup_count(self.backups);
+                storage.set_journal_enabled(self.journal);
+                Box::new(storage)
+            }
+            StorageKind::Sqlite => Box::new(SqliteStorage::open(&file_path)?),
+            StorageKind::Directory => Box::new(DirectoryStorage::new(&file_path)),
+            StorageKind::Memory => Box::new(MemoryStorage::new()),
+        };
+        Ok(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn missing_config_file() {
+        let config = Config::load(Path::new("missing-directory/tant.toml")).unwrap();
+        assert_eq!(config.storage, StorageKind::Json);
+        assert!(config.file.is_none());
+    }
+
+    #[test]
+    fn missing_required_config_file() {
+        let loaded = Config::load_required(Path::new("missing-directory/tant.toml"));
+        assert!(matches!(loaded, Err(ConfigError::NotFound(path)) if path == Path::new("missing-directory/tant.toml")));
+    }
+
+    #[test]
+    fn file_relative_to_config_file() {
+        let directory = env::temp_dir().join(format!("tant-config-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let config_path = directory.join(CONFIG_FILE_NAME);
+        fs::write(&config_path, "storage = \"sqlite\"\nfile = \"tasks.db\"\n").unwrap();
+
+        let config = Config::load(&config_path).unwrap();
+        assert_eq!(config.tasks_file_path(), directory.join("tasks.db").to_string_lossy());
+    }
+
+    #[test]
+    fn unknown_config_field() {
+        assert!(toml::from_str::<Config>("storag = \"sqlite\"").is_err());
+    }
+}
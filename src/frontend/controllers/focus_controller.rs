@@ -9,7 +9,7 @@ pub struct FocusController {
 
 impl FocusController {
     pub fn new(focusable_widgets: Vec<Rc<RefCell<dyn FocusableWidget>>>) -> FocusController {
-        if focusable_widgets.len() > 0 {
+        if !focusable_widgets.is_empty() {
             focusable_widgets[0]
                 .borrow_mut()
                 .focus_state_changed(FocusState::Focused);
@@ -27,19 +27,19 @@ impl FocusController {
     }
 
     pub fn process_input(&mut self, key_code: KeyCode) {
-        if self.focusable_widgets.len() == 0 {
+        if self.focusable_widgets.is_empty() {
             return;
         }
         match key_code {
             KeyCode::Up => {
                 if self.selected_widget > 0 {
-                    if (self.selected_widget as usize) < self.focusable_widgets.len() {
+                    if self.selected_widget < self.focusable_widgets.len() {
                         self.focusable_widgets[self.selected_widget]
                             .borrow_mut()
                             .focus_state_changed(FocusState::NotFocused);
                     }
                     self.selected_widget -= 1;
-                    self.focusable_widgets[self.selected_widget as usize]
+                    self.focusable_widgets[self.selected_widget]
                         .borrow_mut()
                         .focus_state_changed(FocusState::Focused);
                 }
@@ -50,8 +50,8 @@ impl FocusController {
                         .borrow_mut()
                         .focus_state_changed(FocusState::NotFocused);
                     self.selected_widget += 1;
-                    if (self.selected_widget as usize) < self.focusable_widgets.len() {
-                        self.focusable_widgets[self.selected_widget as usize]
+                    if self.selected_widget < self.focusable_widgets.len() {
+                        self.focusable_widgets[self.selected_widget]
                             .borrow_mut()
                             .focus_state_changed(FocusState::Focused);
                     }
@@ -3,12 +3,18 @@ pub use stateful_list::StatefulList;
 pub mod menu;
 
 pub use menu::{Menu, MenuEvent};
-use crate::backend::TaskManager;
+use crate::backend::{TaskError, TaskManager};
+use crate::hooks::HookRunner;
+use crate::workspace::Workspace;
 pub use crate::app::ApplicationBackend;
 use tui::Terminal;
 
 pub struct Logic {
     pub task_manager : TaskManager,
+    pub workspace: Workspace,
+    pub hook_runner: HookRunner,
+    //Error found while loading the tasks, to be shown once the UI is running
+    pub load_error: Option<TaskError>,
 }
 
 pub struct UIContext {
@@ -16,12 +22,12 @@ pub struct UIContext {
 }
 
 impl Logic {
-    pub fn new() -> Logic {
-        let mut task_manager = TaskManager::new(Some(String::from("state.json")));
-        task_manager.initialize();
-        
+    pub fn new(task_manager: TaskManager, workspace: Workspace, hook_runner: HookRunner, load_error: Option<TaskError>) -> Logic {
         Logic {
             task_manager,
+            workspace,
+            hook_runner,
+            load_error,
         }
     }
 }
\ No newline at end of file
@@ -21,7 +21,7 @@ impl<T> StatefulList<T> {
     }
 
     pub fn next(&mut self) {
-        if self.items.len() == 0 {
+        if self.items.is_empty() {
             return;
         }
         let i = match self.state.selected() {
@@ -38,7 +38,7 @@ impl<T> StatefulList<T> {
     }
 
     pub fn previous(&mut self) {
-        if self.items.len() == 0 {
+        if self.items.is_empty() {
             return;
         }
         let i = match self.state.selected() {
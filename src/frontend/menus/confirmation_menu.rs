@@ -0,0 +1,86 @@
+use crate::app::ApplicationBackend;
+use crate::frontend::{
+    controllers::FocusController,
+    core::{Menu, MenuEvent, UIContext},
+    widgets::{BottomBar, BottomBarAction, FocusableWidget, MultipleOptionWidget, Widget},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::{cell::RefCell, rc::Rc};
+use tui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+pub struct ConfirmationMenu {
+    ui_context: Option<Rc<RefCell<UIContext>>>,
+    message: String,
+    options_input: Rc<RefCell<MultipleOptionWidget>>,
+    bottom_bar: BottomBar,
+    focus_controller: FocusController,
+}
+
+impl ConfirmationMenu {
+    pub fn new(message: String, options: &[String]) -> ConfirmationMenu {
+        let mut bottom_bar = BottomBar::new();
+        bottom_bar.add_action(KeyCode::Enter, BottomBarAction::Submit);
+        bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
+
+        let options_input = Rc::new(RefCell::new(MultipleOptionWidget::new(
+            options,
+            Direction::Horizontal,
+        )));
+        let focusable_widgets: Vec<Rc<RefCell<dyn FocusableWidget>>> =
+            vec![Rc::clone(&options_input) as Rc<RefCell<dyn FocusableWidget>>];
+        let focus_controller = FocusController::new(focusable_widgets);
+
+        ConfirmationMenu {
+            ui_context: None,
+            message,
+            options_input,
+            bottom_bar,
+            focus_controller,
+        }
+    }
+}
+
+impl Menu<Option<usize>> for ConfirmationMenu {
+    fn initialize(&mut self, ui_context: Rc<RefCell<UIContext>>) {
+        self.ui_context = Some(Rc::clone(&ui_context));
+    }
+
+    fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
+        let area = self.bottom_bar.render(frame, frame.size());
+        let block = Block::default().borders(Borders::ALL).title("Confirm");
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+            .split(block.inner(area));
+        frame.render_widget(block, area);
+
+        let message = Paragraph::new(self.message.clone())
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(message, chunks[0]);
+        self.options_input.borrow().render(frame, chunks[1]);
+    }
+
+    fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<Option<usize>>> {
+        self.focus_controller.process_input(key.code);
+
+        match key.code {
+            KeyCode::Esc => Some(MenuEvent::Quit(None)),
+            KeyCode::Enter => Some(MenuEvent::Quit(
+                self.options_input.borrow().get_selected_index(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, elapsed_time: std::time::Duration) {
+        self.focus_controller.update(elapsed_time);
+    }
+}
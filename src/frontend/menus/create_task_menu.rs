@@ -1,11 +1,12 @@
 use crate::app::ApplicationBackend;
-use crate::backend::task::TaskId;
+use crate::backend::task::{Task, TaskId};
+use crate::backend::{dates, TaskFields};
 use crate::frontend::{
     controllers::FocusController,
     core::{Logic, Menu, MenuEvent, UIContext},
     widgets::{
-        BottomBar, BottomBarAction, Button, FocusState, FocusableWidget, InputWidget,
-        MultipleOptionWidget, Widget,
+        BottomBar, BottomBarAction, Button, DateInputWidget, FocusState, FocusableWidget, InputWidget,
+        MessageLine, MultipleOptionWidget, TagsInputWidget, Widget,
     },
 };
 use crossterm::event::{KeyCode, KeyEvent};
@@ -23,35 +24,83 @@ pub struct CreateTaskMenu {
     title_input: Rc<RefCell<InputWidget>>,
     description_input: Rc<RefCell<InputWidget>>,
     state_input: Rc<RefCell<MultipleOptionWidget>>,
+    priority_input: Rc<RefCell<MultipleOptionWidget>>,
+    tags_input: Rc<RefCell<TagsInputWidget>>,
+    due_date_input: Rc<RefCell<DateInputWidget>>,
+    start_date_input: Rc<RefCell<DateInputWidget>>,
     accept_button: Rc<RefCell<Button>>,
     bottom_bar: BottomBar,
+    message_line: MessageLine,
     focus_controller: FocusController,
-    parent_task: Option<TaskId>
+    parent_task: Option<TaskId>,
+    edited_task: Option<TaskId>,
 }
 
 impl CreateTaskMenu {
     pub fn new(logic: Rc<RefCell<Logic>>, parent_task: Option<TaskId>) -> CreateTaskMenu {
+        Self::create(logic, parent_task, None)
+    }
+
+    pub fn edit(logic: Rc<RefCell<Logic>>, task: &Task) -> CreateTaskMenu {
+        let menu = Self::create(logic, task.parent_task, Some(task.id));
+        menu.title_input.borrow_mut().set_text(task.title.clone());
+        menu.state_input.borrow_mut().select_option(&task.state);
+        if let Some(priority) = &task.priority {
+            menu.priority_input.borrow_mut().select_option(priority);
+        }
+        menu.tags_input.borrow_mut().set_tags(&task.tags);
+        menu.due_date_input.borrow_mut().set_date(task.due_date);
+        menu.start_date_input.borrow_mut().set_date(task.start_date);
+        menu.description_input.borrow_mut().set_text(task.description.clone());
+        menu
+    }
+
+    fn create(logic: Rc<RefCell<Logic>>, parent_task: Option<TaskId>, edited_task: Option<TaskId>) -> CreateTaskMenu {
         let mut bottom_bar = BottomBar::new();
         bottom_bar.add_action(KeyCode::Enter, BottomBarAction::Submit);
+        bottom_bar.add_action(KeyCode::Right, BottomBarAction::CompleteTag);
         bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
 
         let title_input = Rc::new(RefCell::new(InputWidget::create_text_label()));
         let description_input = Rc::new(RefCell::new(InputWidget::create_text_area()));
+        let states = match edited_task {
+            Some(edited_task) => logic.borrow().task_manager.get_allowed_states(edited_task),
+            None => logic.borrow().task_manager.get_states().clone(),
+        };
         let state_input = Rc::new(RefCell::new(MultipleOptionWidget::new(
-            logic.borrow_mut().task_manager.get_states(),
+            &states,
             Direction::Horizontal,
         )));
+        //The first option leaves the task without a priority
+        let mut priorities = vec![String::from("None")];
+        priorities.extend(logic.borrow().task_manager.get_priorities().iter().map(|priority| priority.name.clone()));
+        let priority_input = Rc::new(RefCell::new(MultipleOptionWidget::new(
+            &priorities,
+            Direction::Horizontal,
+        )));
+        let tags_input = Rc::new(RefCell::new(TagsInputWidget::new(
+            logic.borrow().task_manager.get_tags().clone(),
+        )));
+        let due_date_input = Rc::new(RefCell::new(DateInputWidget::new(dates::end_of_day())));
+        let start_date_input = Rc::new(RefCell::new(DateInputWidget::new(dates::start_of_day())));
         let accept_button = Rc::new(RefCell::new(Button::new(
             String::from("Accept"),
             Alignment::Center,
         )));
 
-        let mut focusable_widgets: Vec<Rc<RefCell<dyn FocusableWidget>>> = Vec::new();
-        focusable_widgets.reserve(4);
+        let mut focusable_widgets: Vec<Rc<RefCell<dyn FocusableWidget>>> = Vec::with_capacity(8);
         let cloned_title = Rc::clone(&title_input);
         focusable_widgets.push(cloned_title);
         let cloned_title = Rc::clone(&state_input);
         focusable_widgets.push(cloned_title);
+        let cloned_title = Rc::clone(&priority_input);
+        focusable_widgets.push(cloned_title);
+        let cloned_title = Rc::clone(&tags_input);
+        focusable_widgets.push(cloned_title);
+        let cloned_title = Rc::clone(&due_date_input);
+        focusable_widgets.push(cloned_title);
+        let cloned_title = Rc::clone(&start_date_input);
+        focusable_widgets.push(cloned_title);
         let cloned_title = Rc::clone(&description_input);
         focusable_widgets.push(cloned_title);
         let cloned_title = Rc::clone(&accept_button);
@@ -64,10 +113,63 @@ impl CreateTaskMenu {
             title_input,
             description_input,
             state_input,
+            priority_input,
+            tags_input,
+            due_date_input,
+            start_date_input,
             accept_button,
             bottom_bar,
+            message_line: MessageLine::new(),
             focus_controller,
             parent_task,
+            edited_task,
+        }
+    }
+
+    fn submit(&mut self) -> Option<MenuEvent<Option<TaskId>>> {
+        let title = self.title_input.borrow().get_current_text();
+        let state = self.state_input.borrow().get_selected_option();
+        let description = self.description_input.borrow().get_current_text();
+        let state = match state {
+            Some(state) if !title.is_empty() && !description.is_empty() => state,
+            _ => {
+                self.message_line.show_error("Title, state and description are required");
+                return None;
+            }
+        };
+
+        let tags = self.tags_input.borrow().get_tags();
+        let priority = match self.priority_input.borrow().get_selected_index() {
+            Some(0) | None => None,
+            Some(_) => self.priority_input.borrow().get_selected_option(),
+        };
+        let task_dates = self.due_date_input.borrow().get_date().and_then(|due_date| {
+            Ok((due_date, self.start_date_input.borrow().get_date()?))
+        });
+        let (due_date, start_date) = match task_dates {
+            Ok(dates) => dates,
+            Err(error) => {
+                self.message_line.show_error(error);
+                return None;
+            }
+        };
+        let fields = TaskFields { title, state, description, tags, priority, due_date, start_date };
+
+        let task_manager = &mut self.logic.borrow_mut().task_manager;
+        let submit_result = if let Some(edited_task) = self.edited_task {
+            task_manager
+                .edit_task(edited_task, fields)
+                .map(|_| edited_task)
+        }
+        else {
+            task_manager.create_task(fields, self.parent_task)
+        };
+        match submit_result {
+            Ok(submitted_task) => Some(MenuEvent::Quit(Some(submitted_task))),
+            Err(error) => {
+                self.message_line.show_error(error);
+                None
+            }
         }
     }
 }
@@ -78,13 +180,16 @@ impl Menu<Option<TaskId>> for CreateTaskMenu {
     }
 
     fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
-        //let task_manager = &mut self.logic.borrow_mut().task_manager;
         let area = self.bottom_bar.render(frame, frame.size());
+        let area = self.message_line.render(frame, area);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(0)
             .constraints(
                 [
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Percentage(72),
@@ -107,14 +212,46 @@ impl Menu<Option<TaskId>> for CreateTaskMenu {
             chunks[1],
             13,
         );
+        render_input_widget_with_title(
+            frame,
+            self.priority_input.borrow(),
+            String::from("Priority: "),
+            chunks[2],
+            13,
+        );
+        render_input_widget_with_title(
+            frame,
+            self.tags_input.borrow(),
+            String::from("Tags: "),
+            chunks[3],
+            13,
+        );
+        let date_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[4]);
+        render_input_widget_with_title(
+            frame,
+            self.due_date_input.borrow(),
+            String::from("Due: "),
+            date_chunks[0],
+            13,
+        );
+        render_input_widget_with_title(
+            frame,
+            self.start_date_input.borrow(),
+            String::from("Start: "),
+            date_chunks[1],
+            8,
+        );
         render_input_widget_with_title(
             frame,
             self.description_input.borrow(),
             String::from("Description: "),
-            chunks[2],
+            chunks[5],
             13,
         );
-        self.accept_button.borrow().render(frame, chunks[3]);
+        self.accept_button.borrow().render(frame, chunks[6]);
     }
 
     fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<Option<TaskId>>> {
@@ -123,23 +260,8 @@ impl Menu<Option<TaskId>> for CreateTaskMenu {
 
         match key.code {
             KeyCode::Esc => return Some(MenuEvent::Quit(None)),
-            KeyCode::Enter => {
-                if self.accept_button.borrow_mut().get_focus_state() == FocusState::Focused {
-                    let title = self.title_input.borrow_mut().get_current_text();
-                    let state = self.state_input.borrow_mut().get_selected_option();
-                    let description = self.description_input.borrow_mut().get_current_text();
-                    if title.len() > 0 && state != None && description.len() > 0 {
-                        let mut logic = self.logic.borrow_mut();
-                        let mut _created_task : Option<TaskId> = None;
-                        if let Some(parent_task) = self.parent_task {
-                            _created_task = logic.task_manager.add_task_with_parent(title, state.unwrap(), description, &parent_task);
-                        }
-                        else {
-                            _created_task = logic.task_manager.add_task(title, state.unwrap(), description);
-                        }
-                        return Some(MenuEvent::Quit(_created_task));
-                    }   
-                }
+            KeyCode::Enter if self.accept_button.borrow().get_focus_state() == FocusState::Focused => {
+                return self.submit();
             }
             _ => (),
         };
@@ -3,16 +3,19 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::app::{execute_menu, ApplicationBackend};
 use crate::backend::task::{Task, TaskId};
-use crate::backend::TaskManager;
+use crate::backend::{dates, time_tracking, DeleteMode, DueStatus, TaskError, TaskManager};
 use crate::frontend::widgets::bottom_bar::BottomBarAction;
-use crate::frontend::widgets::BottomBar;
+use crate::frontend::widgets::{tags_input_widget, BottomBar, MessageLine};
 use crate::frontend::{
     core::{Logic, Menu, MenuEvent, StatefulList, UIContext},
-    menus::CreateTaskMenu,
+    menus::{ConfirmationMenu, CreateTaskMenu, StatePickerMenu, StatesMenu},
 };
+use chrono::{DateTime, Duration, Utc};
 use crossterm::event::{KeyCode, KeyEvent};
 
 use tui::layout::Rect;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
 use tui::widgets::Paragraph;
 use tui::{
     layout::{Constraint, Direction, Layout},
@@ -30,6 +33,18 @@ pub struct MainMenu {
     ui_context: Option<Rc<RefCell<UIContext>>>,
     task_list: StatefulList<TaskUIView>,
     bottom_bar: BottomBar,
+    move_bottom_bar: BottomBar,
+    message_line: MessageLine,
+    moving_task: Option<TaskId>,
+    tag_filter: Option<String>,
+    //Otherwise sibling tasks are in the order they were added
+    sort_by_priority: bool,
+    //Tasks whose start date has not come yet are hidden unless this is set
+    show_deferred: bool,
+    //When the first hidden task has to be shown
+    next_start_date: Option<DateTime<Utc>>,
+    //Time the tasks are shown at, kept up to date by update so running timers count up
+    clock: DateTime<Utc>,
 }
 
 impl MainMenu {
@@ -37,33 +52,358 @@ impl MainMenu {
         let mut bottom_bar = BottomBar::new();
         bottom_bar.add_action(KeyCode::Char('n'), BottomBarAction::CreateTask);
         bottom_bar.add_action(KeyCode::Char('s'), BottomBarAction::CreateTaskWithParent);
+        bottom_bar.add_action(KeyCode::Char('e'), BottomBarAction::EditTask);
+        bottom_bar.add_action(KeyCode::Char('d'), BottomBarAction::DeleteTask);
+        bottom_bar.add_action(KeyCode::Char('m'), BottomBarAction::MoveTask);
+        bottom_bar.add_action(KeyCode::Char('['), BottomBarAction::PreviousState);
+        bottom_bar.add_action(KeyCode::Char(']'), BottomBarAction::NextState);
+        bottom_bar.add_action(KeyCode::Char('c'), BottomBarAction::ChangeState);
+        bottom_bar.add_action(KeyCode::Char('o'), BottomBarAction::ManageStates);
+        bottom_bar.add_action(KeyCode::Char('f'), BottomBarAction::FilterByTag);
+        bottom_bar.add_action(KeyCode::Char('p'), BottomBarAction::SortByPriority);
+        bottom_bar.add_action(KeyCode::Char('h'), BottomBarAction::ShowDeferred);
+        bottom_bar.add_action(KeyCode::Char('t'), BottomBarAction::ToggleTimer);
+        bottom_bar.add_action(KeyCode::Char('u'), BottomBarAction::Undo);
+        bottom_bar.add_action(KeyCode::Char('r'), BottomBarAction::Redo);
         bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
 
+        let mut move_bottom_bar = BottomBar::new();
+        move_bottom_bar.add_action(KeyCode::Enter, BottomBarAction::SetAsParent);
+        move_bottom_bar.add_action(KeyCode::Char('t'), BottomBarAction::MoveToTopLevel);
+        move_bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Cancel);
+
         MainMenu {
             logic: Rc::clone(&logic),
             ui_context: None,
             task_list: StatefulList::new(),
             bottom_bar,
+            move_bottom_bar,
+            message_line: MessageLine::new(),
+            moving_task: None,
+            tag_filter: None,
+            sort_by_priority: false,
+            show_deferred: false,
+            next_start_date: None,
+            clock: Utc::now(),
         }
     }
 
     fn refresh_tasks(&mut self) {
-        let task_manager = &self.logic.borrow_mut().task_manager;
-        let tasks = task_manager.get_tasks();
-        let mut viewed_tasks = HashSet::<TaskId>::new();
-        let mut task_ui_views = Vec::<TaskUIView>::new();
-        tasks.iter().for_each(|task| {
-            if !viewed_tasks.contains(&task.id) {
-                add_subtasks(
-                    &task_manager,
-                    task.id,
-                    &mut viewed_tasks,
-                    &mut task_ui_views,
-                    0,
-                );
-            }
-        });
+        let selected_task_id = self.selected_task().map(|ui_task| ui_task.task.id);
+        let previous_selected_index = self.task_list.state.selected();
+        let mut task_ui_views;
+        let now = Utc::now();
+        let hidden_at = if self.show_deferred { None } else { Some(now) };
+        {
+            let task_manager = &self.logic.borrow().task_manager;
+            let tasks = task_manager.get_tasks();
+            self.next_start_date = tasks
+                .iter()
+                .filter_map(|task| task.start_date.map(|start_date| start_date.with_timezone(&Utc)))
+                .filter(|start_date| *start_date > now)
+                .min();
+            task_ui_views = build_task_ui_views(task_manager, self.sort_by_priority, hidden_at);
+            if let Some(tag_filter) = &self.tag_filter {
+                //Ancestors of the matching tasks are kept so the tree still shows where they are
+                let mut shown_tasks = HashSet::<TaskId>::new();
+                for task in tasks.iter().filter(|task| task.tags.contains(tag_filter)) {
+                    let mut current = Some(task.id);
+                    while let Some(task_id) = current.filter(|task_id| shown_tasks.insert(*task_id)) {
+                        current = task_manager.find_task(task_id).and_then(|task| task.parent_task);
+                    }
+                }
+                task_ui_views.retain(|ui_task| shown_tasks.contains(&ui_task.task.id));
+            }
+        }
         self.task_list = StatefulList::with_items(task_ui_views);
+        if selected_task_id.is_some_and(|task_id| self.select_task(task_id)) {
+            return;
+        }
+        if let Some(previous_selected_index) = previous_selected_index {
+            //The selected task is gone, keep the cursor where it was
+            let last_index = self.task_list.items.len().saturating_sub(1);
+            self.task_list.state.select(Some(previous_selected_index.min(last_index)));
+        }
+    }
+
+    fn select_task(&mut self, task_id: TaskId) -> bool {
+        let index = self
+            .task_list
+            .items
+            .iter()
+            .position(|ui_task| ui_task.task.id == task_id);
+        if index.is_some() {
+            self.task_list.state.select(index);
+        }
+        index.is_some()
+    }
+
+    fn selected_task(&self) -> Option<&TaskUIView> {
+        self.task_list
+            .state
+            .selected()
+            .and_then(|selected_index| self.task_list.items.get(selected_index))
+    }
+
+    fn execute_task_menu(&mut self, menu: CreateTaskMenu) -> Option<MenuEvent<()>> {
+        let mut new_menu: Box<dyn Menu<Option<TaskId>>> = Box::new(menu);
+        let menu_execution_result =
+            execute_menu(&mut new_menu, Rc::clone(self.ui_context.as_ref().unwrap()));
+        match menu_execution_result {
+            Ok(task_id_opt) => {
+                if task_id_opt.is_some() {
+                    self.refresh_tasks();
+                    return Some(MenuEvent::MenuExecutionResult(Ok(())));
+                }
+            }
+            Err(e) => return Some(MenuEvent::MenuExecutionResult(Err(e))),
+        }
+        None
+    }
+
+    fn delete_selected_task(&mut self) -> Option<MenuEvent<()>> {
+        let selected_task = &self.selected_task()?.task;
+        let task_id = selected_task.id;
+        let message = format!("Delete task \"{}\"?", selected_task.title);
+        let (mut options, delete_modes) = if selected_task.child_tasks.is_empty() {
+            (vec![String::from("Delete")], vec![DeleteMode::DeleteChildren])
+        } else {
+            (
+                vec![
+                    String::from("Delete with subtasks"),
+                    String::from("Delete and move subtasks to parent"),
+                ],
+                vec![DeleteMode::DeleteChildren, DeleteMode::ReparentChildren],
+            )
+        };
+        options.push(String::from("Cancel"));
+
+        let mut confirmation_menu: Box<dyn Menu<Option<usize>>> =
+            Box::new(ConfirmationMenu::new(message, &options));
+        let menu_execution_result = execute_menu(
+            &mut confirmation_menu,
+            Rc::clone(self.ui_context.as_ref().unwrap()),
+        );
+        match menu_execution_result {
+            Ok(selected_option) => {
+                let delete_mode = selected_option.and_then(|option| delete_modes.get(option))?;
+                let delete_result = self.logic.borrow_mut().task_manager.delete_task(task_id, *delete_mode);
+                self.handle_task_result(delete_result)
+            }
+            Err(e) => Some(MenuEvent::MenuExecutionResult(Err(e))),
+        }
+    }
+
+    fn shift_selected_task_state(&mut self, forward: bool) -> Option<MenuEvent<()>> {
+        let selected_task = &self.selected_task()?.task;
+        let task_id = selected_task.id;
+        let new_state = {
+            let task_manager = &self.logic.borrow().task_manager;
+            let states = task_manager.get_states();
+            let index = states.iter().position(|state| *state == selected_task.state)?;
+            //Skip the states the workflow does not allow to reach from the current one
+            let candidate_states: Vec<&String> = if forward {
+                states[index + 1..].iter().collect()
+            } else {
+                states[..index].iter().rev().collect()
+            };
+            candidate_states
+                .into_iter()
+                .find(|state| task_manager.can_change_state(task_id, state))?
+                .clone()
+        };
+        self.change_task_state(task_id, new_state)
+    }
+
+    fn pick_selected_task_state(&mut self) -> Option<MenuEvent<()>> {
+        let selected_task = self.selected_task()?.task.clone();
+        let states = self.logic.borrow().task_manager.get_allowed_states(selected_task.id);
+        let mut state_picker_menu: Box<dyn Menu<Option<String>>> =
+            Box::new(StatePickerMenu::new(
+                format!("Change state of \"{}\"", selected_task.title),
+                &states,
+                Some(&selected_task.state),
+            ));
+        let menu_execution_result = execute_menu(
+            &mut state_picker_menu,
+            Rc::clone(self.ui_context.as_ref().unwrap()),
+        );
+        match menu_execution_result {
+            Ok(picked_state) => self.change_task_state(selected_task.id, picked_state?),
+            Err(e) => Some(MenuEvent::MenuExecutionResult(Err(e))),
+        }
+    }
+
+    fn manage_states(&mut self) -> Option<MenuEvent<()>> {
+        let mut states_menu: Box<dyn Menu<()>> = Box::new(StatesMenu::new(Rc::clone(&self.logic)));
+        let menu_execution_result =
+            execute_menu(&mut states_menu, Rc::clone(self.ui_context.as_ref().unwrap()));
+        //States may have been renamed or deleted, so task states have to be reloaded
+        self.refresh_tasks();
+        Some(MenuEvent::MenuExecutionResult(menu_execution_result))
+    }
+
+    fn pick_tag_filter(&mut self) -> Option<MenuEvent<()>> {
+        let mut options = vec![String::from("All tasks")];
+        options.extend(self.logic.borrow().task_manager.get_tags().keys().cloned());
+        let mut tag_picker_menu: Box<dyn Menu<Option<String>>> = Box::new(StatePickerMenu::new(
+            String::from("Filter by tag"),
+            &options,
+            self.tag_filter.as_deref(),
+        ));
+        let menu_execution_result = execute_menu(
+            &mut tag_picker_menu,
+            Rc::clone(self.ui_context.as_ref().unwrap()),
+        );
+        match menu_execution_result {
+            Ok(picked_option) => {
+                //Tags can not contain spaces, so no tag is mistaken for the first option
+                self.tag_filter = Some(picked_option?).filter(|picked_tag| *picked_tag != options[0]);
+                self.refresh_tasks();
+                Some(MenuEvent::MenuExecutionResult(Ok(())))
+            }
+            Err(e) => Some(MenuEvent::MenuExecutionResult(Err(e))),
+        }
+    }
+
+    fn toggle_sort_by_priority(&mut self) -> Option<MenuEvent<()>> {
+        self.sort_by_priority = !self.sort_by_priority;
+        let message = if self.sort_by_priority { "Sorting by priority" } else { "Sorting by creation" };
+        self.message_line.show_info(message);
+        self.refresh_tasks();
+        Some(MenuEvent::MenuExecutionResult(Ok(())))
+    }
+
+    fn toggle_show_deferred(&mut self) -> Option<MenuEvent<()>> {
+        self.show_deferred = !self.show_deferred;
+        let message = if self.show_deferred { "Showing deferred tasks" } else { "Hiding tasks until their start date" };
+        self.message_line.show_info(message);
+        self.refresh_tasks();
+        Some(MenuEvent::MenuExecutionResult(Ok(())))
+    }
+
+    fn toggle_selected_task_timer(&mut self) -> Option<MenuEvent<()>> {
+        let selected_task = &self.selected_task()?.task;
+        let task_id = selected_task.id;
+        let timer_result = {
+            let task_manager = &mut self.logic.borrow_mut().task_manager;
+            let running = task_manager.find_task(task_id).is_some_and(|task| task.running_entry().is_some());
+            if running {
+                task_manager.stop_timer(task_id, Utc::now())
+            }
+            else {
+                task_manager.start_timer(task_id, Utc::now())
+            }
+        };
+        self.handle_task_result(timer_result)
+    }
+
+    fn change_task_state(&mut self, task_id: TaskId, state: String) -> Option<MenuEvent<()>> {
+        let change_result = self.logic.borrow_mut().task_manager.set_task_state(task_id, state);
+        self.handle_task_result(change_result)
+    }
+
+    fn handle_task_result(&mut self, result: Result<(), TaskError>) -> Option<MenuEvent<()>> {
+        if let Err(error) = result {
+            self.message_line.show_error(error);
+            return None;
+        }
+        self.refresh_tasks();
+        Some(MenuEvent::MenuExecutionResult(Ok(())))
+    }
+
+    fn undo_operation(&mut self, redo: bool) -> Option<MenuEvent<()>> {
+        let result = {
+            let task_manager = &mut self.logic.borrow_mut().task_manager;
+            if redo {
+                task_manager.redo()
+            }
+            else {
+                task_manager.undo()
+            }
+        };
+        match result {
+            Ok(Some(description)) => {
+                let verb = if redo { "Redone" } else { "Undone" };
+                self.message_line.show_info(format!("{}: {}", verb, description));
+                self.refresh_tasks();
+                Some(MenuEvent::MenuExecutionResult(Ok(())))
+            }
+            Ok(None) => {
+                let message = if redo { "Nothing to redo" } else { "Nothing to undo" };
+                self.message_line.show_info(message);
+                None
+            }
+            Err(error) => {
+                self.message_line.show_error(error);
+                None
+            }
+        }
+    }
+
+    fn handle_load_error(&mut self, load_error: TaskError) {
+        let backup_path = match load_error {
+            TaskError::CorruptedFile { ref backup_path, .. } => backup_path.clone(),
+            _ => {
+                self.message_line.show_error(load_error);
+                return;
+            }
+        };
+
+        let message = format!("{}. Recover the tasks that can still be read?", load_error);
+        let options = vec![
+            String::from("Recover readable tasks"),
+            String::from("Start with an empty board"),
+        ];
+        let mut confirmation_menu: Box<dyn Menu<Option<usize>>> =
+            Box::new(ConfirmationMenu::new(message, &options));
+        let menu_execution_result = execute_menu(
+            &mut confirmation_menu,
+            Rc::clone(self.ui_context.as_ref().unwrap()),
+        );
+        match menu_execution_result {
+            Ok(Some(0)) => {
+                let recover_result = self.logic.borrow_mut().task_manager.recover_from(&backup_path);
+                match recover_result {
+                    Ok(recovered_tasks) => self.message_line.show_info(format!("Recovered {} tasks", recovered_tasks)),
+                    Err(error) => self.message_line.show_error(error),
+                }
+            }
+            Ok(_) => self.message_line.show_info(format!("The previous tasks are kept in {}", backup_path)),
+            Err(error) => self.message_line.show_error(error),
+        }
+    }
+
+    fn on_key_pressed_while_moving(&mut self, moving_task: TaskId, key: KeyEvent) -> Option<MenuEvent<()>> {
+        let new_parent = match key.code {
+            KeyCode::Up => {
+                self.task_list.previous();
+                return None;
+            }
+            KeyCode::Down => {
+                self.task_list.next();
+                return None;
+            }
+            KeyCode::Enter => Some(self.selected_task()?.task.id),
+            KeyCode::Char('t') | KeyCode::Char('T') => None,
+            KeyCode::Esc => {
+                self.moving_task = None;
+                self.select_task(moving_task);
+                return None;
+            }
+            _ => return None,
+        };
+
+        //Invalid parents (the task itself or one of its subtasks) keep us picking
+        let move_result = self.logic.borrow_mut().task_manager.move_task(moving_task, new_parent);
+        if let Err(error) = move_result {
+            self.message_line.show_error(error);
+            return None;
+        }
+        self.moving_task = None;
+        self.refresh_tasks();
+        self.select_task(moving_task);
+        Some(MenuEvent::MenuExecutionResult(Ok(())))
     }
 
     fn render_tasks(&self, frame: &mut Frame<ApplicationBackend>, rect: Rect) {
@@ -74,7 +414,19 @@ impl MainMenu {
             .map(|_| Constraint::Length(1))
             .collect();
 
-        let block = Block::default().borders(Borders::ALL).title("Task List");
+        let mut title = match self.moving_task.and_then(|task_id| self.logic.borrow().task_manager.find_task(task_id).cloned()) {
+            Some(moving_task) => format!("Pick new parent for \"{}\"", moving_task.title),
+            None => match &self.tag_filter {
+                Some(tag_filter) => format!("Task List - {} - #{}", self.logic.borrow().workspace, tag_filter),
+                None => format!("Task List - {}", self.logic.borrow().workspace),
+            },
+        };
+        //The running timer is shown even when its task is not in the list
+        if let Some(running_task) = self.logic.borrow().task_manager.get_running_timer() {
+            let running_time = running_task.running_entry().map_or(Duration::zero(), |entry| entry.duration(self.clock));
+            title.push_str(&format!(" - ⏱ {} {}", running_task.title, time_tracking::format_timer(running_time)));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
         let render_rect = block.inner(rect);
         frame.render_widget(block, rect);
 
@@ -83,8 +435,10 @@ impl MainMenu {
             .constraints(constraints)
             .split(render_rect);
 
-        for i in 0..self.task_list.items.len() {
-            let ui_task = &self.task_list.items[i];
+        let logic = self.logic.borrow();
+        let tag_colors = logic.task_manager.get_tags();
+        let now = self.clock;
+        for (i, ui_task) in self.task_list.items.iter().enumerate() {
             let offset = ui_task.depth * 4;
             let line_chunks = Layout::default()
                 .direction(Direction::Horizontal)
@@ -94,16 +448,55 @@ impl MainMenu {
                 ])
                 .split(chunks[i]);
             {
-                let mut text_str = String::from("  ");
-                text_str.push_str(&ui_task.task.title);
-                if i == self.task_list.state.selected().unwrap() {
-                    text_str.replace_range(1..2, ">");
+                let selection_marker = if i == self.task_list.state.selected().unwrap() { " >" } else { "  " };
+                let mut spans = vec![Span::raw(selection_marker)];
+                //Tasks without a priority keep the space of the marker so the titles are aligned
+                let priority = ui_task.task.priority.as_ref().and_then(|priority| logic.task_manager.find_priority(priority));
+                match priority {
+                    Some(priority) => spans.push(Span::styled(
+                        "● ",
+                        Style::default().fg(tags_input_widget::to_color(priority.color)),
+                    )),
+                    None => spans.push(Span::raw("  ")),
                 }
-                let text = Paragraph::new(text_str).block(Block::default());
+                let date_style = match logic.task_manager.get_due_status(&ui_task.task, now) {
+                    _ if ui_task.task.is_deferred(now) => Style::default().fg(Color::DarkGray),
+                    DueStatus::Overdue => Style::default().fg(Color::Red),
+                    DueStatus::DueSoon => Style::default().fg(Color::Yellow),
+                    DueStatus::NotDue => Style::default(),
+                };
+                spans.push(Span::styled(format!("{} [{}]", ui_task.task.title, ui_task.task.state), date_style));
+                if let Some(due_date) = &ui_task.task.due_date {
+                    spans.push(Span::styled(format!(" due {}", dates::format_date(due_date)), date_style));
+                }
+                if let Some(start_date) = ui_task.task.start_date.filter(|_| ui_task.task.is_deferred(now)) {
+                    spans.push(Span::styled(format!(" starts {}", dates::format_date(&start_date)), date_style));
+                }
+                for tag in &ui_task.task.tags {
+                    let style = match tag_colors.get(tag) {
+                        Some(tag_color) => Style::default().fg(tags_input_widget::to_color(*tag_color)),
+                        None => Style::default(),
+                    };
+                    spans.push(Span::styled(format!(" #{}", tag), style));
+                }
+                //Subtasks add to the time of their parent
+                let time_spent = logic.task_manager.get_subtree_time_spent(ui_task.task.id, now);
+                if let Some(running_entry) = ui_task.task.running_entry() {
+                    let running_time = time_tracking::format_timer(running_entry.duration(now));
+                    spans.push(Span::styled(format!(" ⏱ {}", running_time), Style::default().fg(Color::Green)));
+                }
+                if time_spent > Duration::zero() {
+                    let time_spent = time_tracking::format_duration(time_spent);
+                    spans.push(Span::styled(format!(" ({})", time_spent), Style::default().fg(Color::DarkGray)));
+                }
+                if self.moving_task == Some(ui_task.task.id) {
+                    spans.push(Span::raw(" (moving)"));
+                }
+                let text = Paragraph::new(Spans::from(spans)).block(Block::default());
                 frame.render_widget(text, line_chunks[1]);
             }
             if offset > 0 {
-                let mut text_str = String::from(" ".repeat(offset-2));
+                let mut text_str = " ".repeat(offset-2);
                 text_str.push_str("└─");
                 let text = Paragraph::new(text_str).block(Block::default());
                 frame.render_widget(text, line_chunks[0]);
@@ -115,30 +508,34 @@ impl MainMenu {
 impl Menu<()> for MainMenu {
     fn initialize(&mut self, ui_context: Rc<RefCell<UIContext>>) {
         self.ui_context = Some(Rc::clone(&ui_context));
+        let load_error = self.logic.borrow_mut().load_error.take();
+        if let Some(load_error) = load_error {
+            self.handle_load_error(load_error);
+        }
         self.refresh_tasks();
     }
 
     fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
-        let area = self.bottom_bar.render(frame, frame.size());
+        let bottom_bar = if self.moving_task.is_some() {
+            &self.move_bottom_bar
+        } else {
+            &self.bottom_bar
+        };
+        let area = bottom_bar.render(frame, frame.size());
+        let area = self.message_line.render(frame, area);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
             .constraints([Constraint::Percentage(50)].as_ref())
             .split(area);
-        //let tasks: Vec<ListItem> = task_manager
-        //    .get_tasks()
-        //    .iter()
-        //    .map(|i| ListItem::new(vec![Spans::from(Span::raw(i.title.clone()))]))
-        //    .collect();
-        //let tasks = List::new(tasks)
-        //    .block(Block::default().borders(Borders::ALL).title("Task List"))
-        //    .highlight_style(Style::default().add_modifier(Modifier::BOLD))
-        //    .highlight_symbol("> ");
-        //frame.render_stateful_widget(tasks, chunks[0], &mut self.task_list.state);
         self.render_tasks(frame, chunks[0]);
     }
 
     fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<()>> {
+        self.message_line.clear();
+        if let Some(moving_task) = self.moving_task {
+            return self.on_key_pressed_while_moving(moving_task, key);
+        }
         match key.code {
             KeyCode::Up => self.task_list.previous(),
             KeyCode::Down => self.task_list.next(),
@@ -147,22 +544,44 @@ impl Menu<()> for MainMenu {
                 if 'n' == pressed_char || 's' == pressed_char {
                     let mut parent_task: Option<TaskId> = None;
                     if 's' == pressed_char {
-                        let selected_index = self.task_list.state.selected().unwrap();
-                        parent_task = Some(self.task_list.items[selected_index].task.id);
-                    }
-                    let mut new_menu: Box<dyn Menu<Option<TaskId>>> =
-                        Box::new(CreateTaskMenu::new(Rc::clone(&self.logic), parent_task));
-                    let menu_execution_result =
-                        execute_menu(&mut new_menu, Rc::clone(self.ui_context.as_ref().unwrap()));
-                    match menu_execution_result {
-                        Ok(created_task_id_opt) => {
-                            if let Some(_) = created_task_id_opt {
-                                self.refresh_tasks();
-                                return Some(MenuEvent::MenuExecutionResult(Ok(())));
-                            }
-                        }
-                        Err(e) => return Some(MenuEvent::MenuExecutionResult(Err(e))),
+                        parent_task = Some(self.selected_task()?.task.id);
                     }
+                    let menu = CreateTaskMenu::new(Rc::clone(&self.logic), parent_task);
+                    return self.execute_task_menu(menu);
+                }
+                if 'e' == pressed_char {
+                    let menu = CreateTaskMenu::edit(Rc::clone(&self.logic), &self.selected_task()?.task);
+                    return self.execute_task_menu(menu);
+                }
+                if 'd' == pressed_char {
+                    return self.delete_selected_task();
+                }
+                if ']' == pressed_char || '[' == pressed_char {
+                    return self.shift_selected_task_state(']' == pressed_char);
+                }
+                if 'c' == pressed_char {
+                    return self.pick_selected_task_state();
+                }
+                if 'o' == pressed_char {
+                    return self.manage_states();
+                }
+                if 'f' == pressed_char {
+                    return self.pick_tag_filter();
+                }
+                if 'p' == pressed_char {
+                    return self.toggle_sort_by_priority();
+                }
+                if 'h' == pressed_char {
+                    return self.toggle_show_deferred();
+                }
+                if 't' == pressed_char {
+                    return self.toggle_selected_task_timer();
+                }
+                if 'u' == pressed_char || 'r' == pressed_char {
+                    return self.undo_operation('r' == pressed_char);
+                }
+                if 'm' == pressed_char {
+                    self.moving_task = Some(self.selected_task()?.task.id);
                 }
             }
             KeyCode::Esc => return Some(MenuEvent::Quit(())),
@@ -171,23 +590,120 @@ impl Menu<()> for MainMenu {
         None
     }
 
-    fn update(&mut self, _elapsed_time: std::time::Duration) {}
+    fn update(&mut self, _elapsed_time: std::time::Duration) {
+        self.clock = Utc::now();
+        if self.next_start_date.is_some_and(|next_start_date| next_start_date <= self.clock) {
+            self.refresh_tasks();
+        }
+        //Hooks run in the background, their failures show up whenever they happen
+        let hook_failures = self.logic.borrow().hook_runner.take_failures();
+        match hook_failures.as_slice() {
+            [] => (),
+            [hook_failure] => self.message_line.show_error(hook_failure),
+            [.., last_failure] => {
+                let message = format!("{} hooks failed, the last one: {}", hook_failures.len(), last_failure);
+                self.message_line.show_error(message);
+            }
+        }
+    }
+}
+
+//Every top level task followed by its subtasks, so each task is shown once whatever the order of the ids
+fn build_task_ui_views(task_manager: &TaskManager, sort_by_priority: bool, hidden_at: Option<DateTime<Utc>>) -> Vec<TaskUIView> {
+    let mut root_tasks: Vec<TaskId> = task_manager
+        .get_tasks()
+        .iter()
+        .filter(|task| task.parent_task.is_none())
+        .map(|task| task.id)
+        .collect();
+    if sort_by_priority {
+        task_manager.sort_by_priority(&mut root_tasks);
+    }
+    let mut ui_tasks = Vec::new();
+    root_tasks
+        .iter()
+        .for_each(|task_id| add_subtasks(task_manager, *task_id, &mut ui_tasks, 0, sort_by_priority, hidden_at));
+    ui_tasks
 }
 
 fn add_subtasks(
     task_manager: &TaskManager,
     task_id: TaskId,
-    viewed_tasks: &mut HashSet<TaskId>,
     ui_tasks: &mut Vec<TaskUIView>,
     depth: usize,
+    sort_by_priority: bool,
+    hidden_at: Option<DateTime<Utc>>,
 ) {
-    let task = task_manager.find_task(task_id).unwrap();
+    let task = match task_manager.find_task(task_id) {
+        Some(task) => task,
+        None => return,
+    };
+    //Subtasks of a deferred task are deferred with it
+    if hidden_at.is_some_and(|now| task.is_deferred(now)) {
+        return;
+    }
     ui_tasks.push(TaskUIView {
         task: task.clone(),
         depth,
     });
-    viewed_tasks.insert(task_id);
-    task.child_tasks
+    let mut child_tasks = task.child_tasks.clone();
+    if sort_by_priority {
+        task_manager.sort_by_priority(&mut child_tasks);
+    }
+    child_tasks
         .iter()
-        .for_each(|t| add_subtasks(task_manager, *t, viewed_tasks, ui_tasks, depth + 1));
+        .for_each(|t| add_subtasks(task_manager, *t, ui_tasks, depth + 1, sort_by_priority, hidden_at));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{storage::MemoryStorage, TaskFields};
+
+    fn create_task_manager() -> TaskManager {
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
+        task_manager
+    }
+
+    fn rows(task_manager: &TaskManager, sort_by_priority: bool) -> Vec<(String, usize)> {
+        build_task_ui_views(task_manager, sort_by_priority, None)
+            .into_iter()
+            .map(|ui_task| (ui_task.task.title, ui_task.depth))
+            .collect()
+    }
+
+    #[test]
+    fn task_moved_under_newer_task() {
+        let mut task_manager = create_task_manager();
+        let first = task_manager.add_task(String::from("First"), String::from("Open"), String::new()).unwrap();
+        let second = task_manager.add_task(String::from("Second"), String::from("Open"), String::new()).unwrap();
+        assert!(task_manager.move_task(first, Some(second)).is_ok());
+
+        assert_eq!(rows(&task_manager, false), vec![(String::from("Second"), 0), (String::from("First"), 1)]);
+    }
+
+    #[test]
+    fn high_priority_subtask_of_low_priority_task() {
+        let mut task_manager = create_task_manager();
+        let fields = |title: &str, priority: &str| TaskFields {
+            title: String::from(title),
+            state: String::from("Open"),
+            priority: Some(String::from(priority)),
+            ..TaskFields::default()
+        };
+        let low = task_manager.create_task(fields("Low", "Low"), None).unwrap();
+        assert!(task_manager.create_task(fields("Medium", "Medium"), None).is_ok());
+        assert!(task_manager.create_task(fields("Low subtask", "Low"), Some(low)).is_ok());
+        assert!(task_manager.create_task(fields("High subtask", "High"), Some(low)).is_ok());
+
+        //Subtasks are sorted under their parent, never among the top level tasks
+        let expected_rows = vec![
+            (String::from("Medium"), 0),
+            (String::from("Low"), 0),
+            (String::from("High subtask"), 1),
+            (String::from("Low subtask"), 1),
+        ];
+        assert_eq!(rows(&task_manager, true), expected_rows);
+    }
 }
@@ -2,4 +2,16 @@ pub mod main_menu;
 pub use main_menu::MainMenu;
 
 pub mod create_task_menu;
-pub use create_task_menu::CreateTaskMenu;
\ No newline at end of file
+pub use create_task_menu::CreateTaskMenu;
+
+pub mod confirmation_menu;
+pub use confirmation_menu::ConfirmationMenu;
+
+pub mod state_picker_menu;
+pub use state_picker_menu::StatePickerMenu;
+
+pub mod text_input_menu;
+pub use text_input_menu::TextInputMenu;
+
+pub mod states_menu;
+pub use states_menu::StatesMenu;
\ No newline at end of file
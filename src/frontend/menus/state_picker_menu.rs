@@ -0,0 +1,71 @@
+use crate::app::ApplicationBackend;
+use crate::frontend::{
+    core::{Menu, MenuEvent, UIContext},
+    widgets::{BottomBar, BottomBarAction, FocusState, FocusableWidget, MultipleOptionWidget, Widget},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::{cell::RefCell, rc::Rc};
+use tui::{
+    layout::Direction,
+    widgets::{Block, Borders},
+    Frame,
+};
+
+pub struct StatePickerMenu {
+    ui_context: Option<Rc<RefCell<UIContext>>>,
+    title: String,
+    state_input: MultipleOptionWidget,
+    bottom_bar: BottomBar,
+}
+
+impl StatePickerMenu {
+    pub fn new(title: String, states: &[String], selected_state: Option<&str>) -> StatePickerMenu {
+        let mut bottom_bar = BottomBar::new();
+        bottom_bar.add_action(KeyCode::Enter, BottomBarAction::Submit);
+        bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
+
+        let mut state_input = MultipleOptionWidget::new(states, Direction::Vertical);
+        if let Some(selected_state) = selected_state {
+            state_input.select_option(selected_state);
+        }
+        state_input.focus_state_changed(FocusState::Focused);
+
+        StatePickerMenu {
+            ui_context: None,
+            title,
+            state_input,
+            bottom_bar,
+        }
+    }
+}
+
+impl Menu<Option<String>> for StatePickerMenu {
+    fn initialize(&mut self, ui_context: Rc<RefCell<UIContext>>) {
+        self.ui_context = Some(Rc::clone(&ui_context));
+    }
+
+    fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
+        let area = self.bottom_bar.render(frame, frame.size());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.clone());
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+        self.state_input.render(frame, inner_area);
+    }
+
+    fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<Option<String>>> {
+        match key.code {
+            KeyCode::Esc => Some(MenuEvent::Quit(None)),
+            KeyCode::Enter => Some(MenuEvent::Quit(self.state_input.get_selected_option())),
+            key_code => {
+                self.state_input.process_input(key_code);
+                None
+            }
+        }
+    }
+
+    fn update(&mut self, elapsed_time: std::time::Duration) {
+        self.state_input.update(elapsed_time);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::app::{execute_menu, ApplicationBackend};
+use crate::backend::TaskError;
+use crate::frontend::{
+    core::{Logic, Menu, MenuEvent, StatefulList, UIContext},
+    menus::{StatePickerMenu, TextInputMenu},
+    widgets::{BottomBar, BottomBarAction, MessageLine},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::{cell::RefCell, io, rc::Rc};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+
+pub struct StatesMenu {
+    logic: Rc<RefCell<Logic>>,
+    ui_context: Option<Rc<RefCell<UIContext>>>,
+    state_list: StatefulList<String>,
+    bottom_bar: BottomBar,
+    message_line: MessageLine,
+}
+
+impl StatesMenu {
+    pub fn new(logic: Rc<RefCell<Logic>>) -> StatesMenu {
+        let mut bottom_bar = BottomBar::new();
+        bottom_bar.add_action(KeyCode::Char('a'), BottomBarAction::AddState);
+        bottom_bar.add_action(KeyCode::Char('r'), BottomBarAction::RenameState);
+        bottom_bar.add_action(KeyCode::Char('d'), BottomBarAction::DeleteState);
+        bottom_bar.add_action(KeyCode::Char('['), BottomBarAction::MoveUp);
+        bottom_bar.add_action(KeyCode::Char(']'), BottomBarAction::MoveDown);
+        bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
+
+        StatesMenu {
+            logic: Rc::clone(&logic),
+            ui_context: None,
+            state_list: StatefulList::new(),
+            bottom_bar,
+            message_line: MessageLine::new(),
+        }
+    }
+
+    fn refresh_states(&mut self, selected_index: Option<usize>) {
+        let states = self.logic.borrow().task_manager.get_states().clone();
+        let last_index = states.len().saturating_sub(1);
+        self.state_list = StatefulList::with_items(states);
+        if let Some(selected_index) = selected_index {
+            self.state_list.state.select(Some(selected_index.min(last_index)));
+        }
+    }
+
+    fn selected_state(&self) -> Option<String> {
+        self.state_list
+            .state
+            .selected()
+            .and_then(|selected_index| self.state_list.items.get(selected_index))
+            .cloned()
+    }
+
+    fn execute_submenu<T>(&self, menu: impl Menu<Option<T>> + 'static) -> Result<Option<T>, io::Error> {
+        let mut menu: Box<dyn Menu<Option<T>>> = Box::new(menu);
+        execute_menu(&mut menu, Rc::clone(self.ui_context.as_ref().unwrap()))
+    }
+
+    fn add_state(&mut self) -> Option<MenuEvent<()>> {
+        let menu = TextInputMenu::new(String::from("New state"), String::new());
+        let new_state = match self.execute_submenu(menu) {
+            Ok(new_state) => new_state?,
+            Err(e) => return Some(MenuEvent::MenuExecutionResult(Err(e))),
+        };
+        let add_result = self.logic.borrow_mut().task_manager.add_state(new_state);
+        self.handle_state_result(add_result, Some(self.state_list.items.len()))
+    }
+
+    fn rename_selected_state(&mut self) -> Option<MenuEvent<()>> {
+        let state = self.selected_state()?;
+        let menu = TextInputMenu::new(format!("Rename \"{}\"", state), state.clone());
+        let new_name = match self.execute_submenu(menu) {
+            Ok(new_name) => new_name?,
+            Err(e) => return Some(MenuEvent::MenuExecutionResult(Err(e))),
+        };
+        let rename_result = self.logic.borrow_mut().task_manager.rename_state(&state, new_name);
+        self.handle_state_result(rename_result, self.state_list.state.selected())
+    }
+
+    fn delete_selected_state(&mut self) -> Option<MenuEvent<()>> {
+        let state = self.selected_state()?;
+        let migration_states: Vec<String> = self
+            .state_list
+            .items
+            .iter()
+            .filter(|other_state| **other_state != state)
+            .cloned()
+            .collect();
+        if migration_states.is_empty() {
+            return None;
+        }
+        let menu = StatePickerMenu::new(
+            format!("Delete \"{}\" and move its tasks to", state),
+            &migration_states,
+            None,
+        );
+        let migration_state = match self.execute_submenu(menu) {
+            Ok(migration_state) => migration_state?,
+            Err(e) => return Some(MenuEvent::MenuExecutionResult(Err(e))),
+        };
+        let delete_result = self.logic.borrow_mut().task_manager.delete_state(&state, &migration_state);
+        self.handle_state_result(delete_result, self.state_list.state.selected())
+    }
+
+    fn move_selected_state(&mut self, up: bool) -> Option<MenuEvent<()>> {
+        let state = self.selected_state()?;
+        let index = self.state_list.state.selected()?;
+        let new_index = if up { index.checked_sub(1)? } else { index + 1 };
+        let move_result = self.logic.borrow_mut().task_manager.move_state(&state, new_index);
+        self.handle_state_result(move_result, Some(new_index))
+    }
+
+    fn handle_state_result(&mut self, result: Result<(), TaskError>, selected_index: Option<usize>) -> Option<MenuEvent<()>> {
+        if let Err(error) = result {
+            self.message_line.show_error(error);
+            return None;
+        }
+        self.refresh_states(selected_index);
+        Some(MenuEvent::MenuExecutionResult(Ok(())))
+    }
+}
+
+impl Menu<()> for StatesMenu {
+    fn initialize(&mut self, ui_context: Rc<RefCell<UIContext>>) {
+        self.ui_context = Some(Rc::clone(&ui_context));
+        self.refresh_states(None);
+    }
+
+    fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
+        let area = self.bottom_bar.render(frame, frame.size());
+        let area = self.message_line.render(frame, area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(area);
+
+        let task_manager = &self.logic.borrow().task_manager;
+        let states: Vec<ListItem> = self
+            .state_list
+            .items
+            .iter()
+            .map(|state| {
+                let tasks_in_state = task_manager.count_tasks_in_state(state);
+                ListItem::new(format!("{} ({} tasks)", state, tasks_in_state))
+            })
+            .collect();
+        let states = List::new(states)
+            .block(Block::default().borders(Borders::ALL).title("States"))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(states, chunks[0], &mut self.state_list.state);
+    }
+
+    fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<()>> {
+        self.message_line.clear();
+        match key.code {
+            KeyCode::Up => self.state_list.previous(),
+            KeyCode::Down => self.state_list.next(),
+            KeyCode::Char(c) => match c.to_ascii_lowercase() {
+                'a' => return self.add_state(),
+                'r' => return self.rename_selected_state(),
+                'd' => return self.delete_selected_state(),
+                '[' => return self.move_selected_state(true),
+                ']' => return self.move_selected_state(false),
+                _ => (),
+            },
+            KeyCode::Esc => return Some(MenuEvent::Quit(())),
+            _ => (),
+        }
+        None
+    }
+
+    fn update(&mut self, _elapsed_time: std::time::Duration) {}
+}
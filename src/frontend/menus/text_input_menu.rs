@@ -0,0 +1,78 @@
+use crate::app::ApplicationBackend;
+use crate::frontend::{
+    core::{Menu, MenuEvent, UIContext},
+    widgets::{BottomBar, BottomBarAction, FocusState, FocusableWidget, InputWidget, Widget},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::{cell::RefCell, rc::Rc};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    widgets::{Block, Borders},
+    Frame,
+};
+
+pub struct TextInputMenu {
+    ui_context: Option<Rc<RefCell<UIContext>>>,
+    title: String,
+    text_input: InputWidget,
+    bottom_bar: BottomBar,
+}
+
+impl TextInputMenu {
+    pub fn new(title: String, initial_text: String) -> TextInputMenu {
+        let mut bottom_bar = BottomBar::new();
+        bottom_bar.add_action(KeyCode::Enter, BottomBarAction::Submit);
+        bottom_bar.add_action(KeyCode::Esc, BottomBarAction::Exit);
+
+        let mut text_input = InputWidget::create_text_label();
+        text_input.set_text(initial_text);
+        text_input.focus_state_changed(FocusState::Focused);
+
+        TextInputMenu {
+            ui_context: None,
+            title,
+            text_input,
+            bottom_bar,
+        }
+    }
+}
+
+impl Menu<Option<String>> for TextInputMenu {
+    fn initialize(&mut self, ui_context: Rc<RefCell<UIContext>>) {
+        self.ui_context = Some(Rc::clone(&ui_context));
+    }
+
+    fn render(&mut self, frame: &mut Frame<ApplicationBackend>) {
+        let area = self.bottom_bar.render(frame, frame.size());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.clone());
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(block.inner(area));
+        frame.render_widget(block, area);
+        self.text_input.render(frame, chunks[0]);
+    }
+
+    fn on_key_pressed(&mut self, key: KeyEvent) -> Option<MenuEvent<Option<String>>> {
+        match key.code {
+            KeyCode::Esc => Some(MenuEvent::Quit(None)),
+            KeyCode::Enter => {
+                let text = self.text_input.get_current_text();
+                if text.trim().is_empty() {
+                    return None;
+                }
+                Some(MenuEvent::Quit(Some(text.trim().to_string())))
+            }
+            key_code => {
+                self.text_input.process_input(key_code);
+                None
+            }
+        }
+    }
+
+    fn update(&mut self, elapsed_time: std::time::Duration) {
+        self.text_input.update(elapsed_time);
+    }
+}
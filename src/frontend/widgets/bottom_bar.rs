@@ -4,21 +4,52 @@ use crate::frontend::core::ApplicationBackend;
 use crossterm::event::KeyCode;
 use tui::{
     layout::{Constraint, Direction, Layout, Rect, Alignment},
-    Frame, widgets::Paragraph, style::{Style, Color},
+    Frame, widgets::{Paragraph, Wrap}, style::{Style, Color},
 };
 
 #[derive(Copy, Clone)]
 pub enum BottomBarAction {
     CreateTask,
     CreateTaskWithParent,
+    EditTask,
+    DeleteTask,
+    MoveTask,
+    NextState,
+    PreviousState,
+    ChangeState,
+    ManageStates,
+    Undo,
+    Redo,
+    AddState,
+    RenameState,
+    DeleteState,
+    MoveUp,
+    MoveDown,
+    SetAsParent,
+    MoveToTopLevel,
+    FilterByTag,
+    SortByPriority,
+    ShowDeferred,
+    ToggleTimer,
+    CompleteTag,
     Submit,
+    Cancel,
     Exit,
 }
 
 struct BottomBarActionConfig {
     key: KeyCode,
-    action: BottomBarAction, //#pau_todo do we really need this?
-    description: String,
+    action: BottomBarAction,
+}
+
+impl BottomBarActionConfig {
+    fn description(&self) -> String {
+        format!(
+            "[{}] {}",
+            key_to_string(self.key),
+            get_bottom_bar_action_description(self.action)
+        )
+    }
 }
 
 pub struct BottomBar {
@@ -33,15 +64,7 @@ impl BottomBar {
     }
 
     pub fn add_action(&mut self, key: KeyCode, action: BottomBarAction) {
-        self.actions.push(BottomBarActionConfig {
-            key,
-            action,
-            description: format!(
-                "[{}] {}",
-                key_to_string(key),
-                get_bottom_bar_action_description(action)
-            ),
-        });
+        self.actions.push(BottomBarActionConfig { key, action });
     }
 
     pub fn render(&self, frame: &mut Frame<ApplicationBackend>, area: Rect) -> Rect {
@@ -55,13 +78,13 @@ impl BottomBar {
             .constraints([Constraint::Max(100), Constraint::Length(3)].as_ref())
             .split(frame.size());
         let full_bottom_bar_string = self.actions.iter().rev().fold("".to_string(), |mut acc, action| {
-            if acc.len() > 0 {
+            if !acc.is_empty() {
                 acc.insert_str(0, " | ");
             }
-            acc.insert_str(0,&action.description);
-            return acc;
+            acc.insert_str(0,&action.description());
+            acc
         });
-        let bottom_bar_paragraph = Paragraph::new(full_bottom_bar_string).style(Style::default().fg(Color::White).bg(Color::Black)).alignment(Alignment::Center);
+        let bottom_bar_paragraph = Paragraph::new(full_bottom_bar_string).style(Style::default().fg(Color::White).bg(Color::Black)).alignment(Alignment::Center).wrap(Wrap { trim: true });
         frame.render_widget(bottom_bar_paragraph, chunks[1]);
         chunks[0]
     }
@@ -73,6 +96,28 @@ fn get_bottom_bar_action_description(action: BottomBarAction) -> String {
         BottomBarAction::Submit => "Submit",
         BottomBarAction::CreateTask => "Create task",
         BottomBarAction::CreateTaskWithParent => "Create with parent",
+        BottomBarAction::EditTask => "Edit task",
+        BottomBarAction::DeleteTask => "Delete task",
+        BottomBarAction::MoveTask => "Move task",
+        BottomBarAction::NextState => "Next state",
+        BottomBarAction::PreviousState => "Previous state",
+        BottomBarAction::ChangeState => "Change state",
+        BottomBarAction::ManageStates => "Manage states",
+        BottomBarAction::Undo => "Undo",
+        BottomBarAction::Redo => "Redo",
+        BottomBarAction::AddState => "Add state",
+        BottomBarAction::RenameState => "Rename state",
+        BottomBarAction::DeleteState => "Delete state",
+        BottomBarAction::MoveUp => "Move up",
+        BottomBarAction::MoveDown => "Move down",
+        BottomBarAction::SetAsParent => "Set as parent",
+        BottomBarAction::MoveToTopLevel => "Move to top level",
+        BottomBarAction::FilterByTag => "Filter by tag",
+        BottomBarAction::SortByPriority => "Sort by priority",
+        BottomBarAction::ShowDeferred => "Show deferred",
+        BottomBarAction::ToggleTimer => "Start/stop timer",
+        BottomBarAction::CompleteTag => "Complete tag",
+        BottomBarAction::Cancel => "Cancel",
     }
     .to_string()
 }
@@ -84,6 +129,7 @@ fn key_to_string(key_code: KeyCode) -> String {
     match key_code {
         KeyCode::Esc => "Esc",
         KeyCode::Enter => "Enter",
+        KeyCode::Right => "Right",
         _ => {
             panic!("Key not handled");
         }
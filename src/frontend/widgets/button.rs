@@ -47,7 +47,7 @@ impl FocusableWidget for Button {
     }
 
     fn get_focus_state(&self) -> FocusState {
-        self.focus_state.clone()
+        self.focus_state
     }
 
     fn process_input(&mut self, _key_code: KeyCode) {
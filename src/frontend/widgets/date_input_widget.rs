@@ -0,0 +1,97 @@
+use crate::backend::dates;
+use crate::frontend::core::ApplicationBackend;
+use chrono::{DateTime, FixedOffset, Local, NaiveTime};
+use crossterm::event::KeyCode;
+use std::time::Duration;
+use tui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::{FocusState, FocusableWidget, InputWidget, Widget};
+
+/// Input for an optional date, in any of the formats of `dates::parse_date`. Shows the date it is read as
+/// next to what is written, or the text in red when it is not a date.
+pub struct DateInputWidget {
+    input: InputWidget,
+    //Time of dates written without one
+    default_time: NaiveTime,
+    //Date being edited, kept as it was while its text is not changed so its time zone is not lost
+    initial_date: Option<(String, DateTime<FixedOffset>)>,
+}
+
+impl DateInputWidget {
+    pub fn new(default_time: NaiveTime) -> DateInputWidget {
+        DateInputWidget {
+            input: InputWidget::create_text_label(),
+            default_time,
+            initial_date: None,
+        }
+    }
+
+    pub fn set_date(&mut self, date: Option<DateTime<FixedOffset>>) {
+        self.initial_date = date.map(|date| (dates::format_date(&date), date));
+        let text = self.initial_date.as_ref().map(|(text, _)| text.clone()).unwrap_or_default();
+        self.input.set_text(text);
+    }
+
+    /// The date written, `None` if there is nothing written.
+    pub fn get_date(&self) -> Result<Option<DateTime<FixedOffset>>, String> {
+        let text = self.input.get_current_text();
+        match &self.initial_date {
+            _ if text.trim().is_empty() => Ok(None),
+            Some((initial_text, initial_date)) if *initial_text == text => Ok(Some(*initial_date)),
+            _ => dates::parse_date(&text, self.default_time, Local::now().date_naive()).map(Some),
+        }
+    }
+}
+
+impl Widget for DateInputWidget {
+    fn render(&self, frame: &mut Frame<ApplicationBackend>, area: Rect) {
+        let text = self.input.get_current_text();
+        let mut spans = Vec::new();
+        match self.get_date() {
+            Ok(Some(date)) => {
+                spans.push(Span::raw(text.clone()));
+                let date_text = dates::format_date(&date);
+                if date_text != text {
+                    spans.push(Span::styled(format!("  {}", date_text), Style::default().fg(Color::DarkGray)));
+                }
+            }
+            Ok(None) if self.input.get_focus_state() == FocusState::NotFocused => {
+                spans.push(Span::styled("YYYY-MM-DD, today, +3d…", Style::default().fg(Color::DarkGray)));
+            }
+            Ok(None) => spans.push(Span::raw(text.clone())),
+            Err(_) => spans.push(Span::styled(text.clone(), Style::default().fg(Color::Red))),
+        }
+        //The cursor goes after what is written, before the date it is read as
+        if self.input.is_cursor_showing() {
+            spans.insert(1, Span::raw("|"));
+        }
+        let paragraph = Paragraph::new(Spans::from(spans))
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        frame.render_widget(paragraph, area)
+    }
+}
+
+impl FocusableWidget for DateInputWidget {
+    fn focus_state_changed(&mut self, focus_state: FocusState) {
+        self.input.focus_state_changed(focus_state);
+    }
+
+    fn get_focus_state(&self) -> FocusState {
+        self.input.get_focus_state()
+    }
+
+    fn process_input(&mut self, key_code: KeyCode) {
+        self.input.process_input(key_code);
+    }
+
+    fn update(&mut self, duration: Duration) {
+        self.input.update(duration);
+    }
+}
@@ -47,6 +47,18 @@ impl InputWidget {
     pub fn get_current_text(&self) -> String {
         self.text.clone()
     }
+
+    pub fn is_cursor_showing(&self) -> bool {
+        self.cursor_state.as_ref().is_some_and(|cursor_state| cursor_state.is_cursor_showing)
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        if !self.allow_new_lines {
+            self.text = text.replace('\n', " ");
+            return;
+        }
+        self.text = text;
+    }
 }
 
 impl Widget for InputWidget {
@@ -87,7 +99,7 @@ impl FocusableWidget for InputWidget {
 
     fn process_input(&mut self, key_code: KeyCode) {
         if self.focus_state == FocusState::NotFocused {
-            return ();
+            return;
         }
         let mut modified_text = false;
         match key_code {
@@ -99,11 +111,9 @@ impl FocusableWidget for InputWidget {
                 self.text.pop();
                 modified_text = true;
             }
-            KeyCode::Enter => {
-                if self.allow_new_lines {
-                    self.text.push('\n');
-                    modified_text = true;
-                }
+            KeyCode::Enter if self.allow_new_lines => {
+                self.text.push('\n');
+                modified_text = true;
             }
             _ => (),
         };
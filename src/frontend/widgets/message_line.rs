@@ -0,0 +1,52 @@
+use std::fmt::Display;
+
+use crate::frontend::core::ApplicationBackend;
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::Paragraph,
+    Frame,
+};
+
+pub struct MessageLine {
+    message: Option<String>,
+    color: Color,
+}
+
+impl MessageLine {
+    pub fn new() -> MessageLine {
+        MessageLine { message: None, color: Color::Red }
+    }
+
+    pub fn show_error(&mut self, error: impl Display) {
+        self.message = Some(error.to_string());
+        self.color = Color::Red;
+    }
+
+    pub fn show_info(&mut self, info: impl Display) {
+        self.message = Some(info.to_string());
+        self.color = Color::Green;
+    }
+
+    pub fn clear(&mut self) {
+        self.message = None;
+    }
+
+    /// Renders the message at the bottom of `area`, returning the space left above it.
+    pub fn render(&self, frame: &mut Frame<ApplicationBackend>, area: Rect) -> Rect {
+        let message = match &self.message {
+            Some(message) => message,
+            None => return area,
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(0)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(area);
+        let message_paragraph = Paragraph::new(message.clone())
+            .style(Style::default().fg(self.color).bg(Color::Black));
+        frame.render_widget(message_paragraph, chunks[1]);
+        chunks[0]
+    }
+}
@@ -13,6 +13,15 @@ pub use multiple_option_widget::MultipleOptionWidget;
 pub mod button;
 pub use button::Button;
 
+pub mod message_line;
+pub use message_line::MessageLine;
+
+pub mod tags_input_widget;
+pub use tags_input_widget::TagsInputWidget;
+
+pub mod date_input_widget;
+pub use date_input_widget::DateInputWidget;
+
 use tui::{Frame, layout::Rect};
 use super::core::ApplicationBackend;
 use crossterm::event::KeyCode;
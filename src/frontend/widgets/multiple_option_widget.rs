@@ -20,11 +20,11 @@ pub struct MultipleOptionWidget {
 }
 
 impl MultipleOptionWidget {
-    pub fn new(options: &Vec<String>, direction: Direction) -> MultipleOptionWidget {
-        let selected_option = if options.len() > 0 { Some(0) } else { None };
+    pub fn new(options: &[String], direction: Direction) -> MultipleOptionWidget {
+        let selected_option = if !options.is_empty() { Some(0) } else { None };
         MultipleOptionWidget {
             selected_option,
-            options: options.clone(),
+            options: options.to_vec(),
             direction,
             focus_state: FocusState::NotFocused,
         }
@@ -34,13 +34,23 @@ impl MultipleOptionWidget {
         if let Some(selected_option) = self.selected_option {
             return Some(self.options[selected_option as usize].clone());
         }
-        return None;
+        None
+    }
+
+    pub fn get_selected_index(&self) -> Option<usize> {
+        self.selected_option.map(|selected_option| selected_option as usize)
+    }
+
+    pub fn select_option(&mut self, option: &str) {
+        if let Some(index) = self.options.iter().position(|o| o == option) {
+            self.selected_option = Some(index as u32);
+        }
     }
 }
 
 impl Widget for MultipleOptionWidget {
     fn render(&self, frame: &mut Frame<ApplicationBackend>, area: Rect) {
-        if self.options.len() == 0 {
+        if self.options.is_empty() {
             return;
         }
         let mut constraints: Vec<Constraint> = Vec::new();
@@ -49,6 +59,11 @@ impl Widget for MultipleOptionWidget {
             .options
             .iter()
             .for_each(|option| {
+                if self.direction == Direction::Vertical {
+                    constraints.push(Constraint::Length(1));
+                    constraints.push(Constraint::Length(0));
+                    return;
+                }
                 let mut length: u16 = option.len() as u16;
                 if selected_option.eq(option) && self.focus_state == FocusState::Focused {
                     length += 1;
@@ -61,14 +76,14 @@ impl Widget for MultipleOptionWidget {
             .margin(1)
             .constraints(constraints)
             .split(area);
-        for i in 0..chunks.len() {
+        for (i, chunk) in chunks.iter().enumerate() {
             if (i % 2) == 1 {
                 continue;
             }
             let elem = i/2;
             let mut style = Style::default().fg(Color::White).bg(Color::Black);
             let mut text_str = self.options[elem].clone();
-            if let Some(selected_input) = self.selected_option.clone() {
+            if let Some(selected_input) = self.selected_option {
                 if selected_input == elem as u32 {
                     style = style.add_modifier(Modifier::UNDERLINED);
                     if self.focus_state == FocusState::Focused {
@@ -79,7 +94,7 @@ impl Widget for MultipleOptionWidget {
             let text = Paragraph::new(text_str)
                 .block(Block::default())
                 .style(style);
-            frame.render_widget(text, chunks[i])
+            frame.render_widget(text, *chunk)
         }
     }
 }
@@ -94,11 +109,15 @@ impl FocusableWidget for MultipleOptionWidget {
     }
 
     fn process_input(&mut self, key_code: KeyCode) {
-        if self.options.len() == 0 {
+        if self.options.is_empty() {
             return;
         }
+        let (previous_key, next_key) = match self.direction {
+            Direction::Horizontal => (KeyCode::Left, KeyCode::Right),
+            Direction::Vertical => (KeyCode::Up, KeyCode::Down),
+        };
         match key_code {
-            KeyCode::Left => {
+            key_code if key_code == previous_key => {
                 if let Some(ref mut selected_option) = self.selected_option {
                     if *selected_option > 0 {
                         *selected_option -= 1;
@@ -107,7 +126,7 @@ impl FocusableWidget for MultipleOptionWidget {
                     self.selected_option = Some(0);
                 }
             }
-            KeyCode::Right => {
+            key_code if key_code == next_key => {
                 if let Some(ref mut selected_option) = self.selected_option {
                     if *selected_option < self.options.len() as u32 - 1 {
                         *selected_option += 1;
@@ -0,0 +1,125 @@
+use crate::backend::{tag, TagColor};
+use crate::frontend::core::ApplicationBackend;
+use crossterm::event::KeyCode;
+use std::{collections::{BTreeMap, BTreeSet}, time::Duration};
+use tui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::{FocusState, FocusableWidget, InputWidget, Widget};
+
+/// Input for the tags of a task, separated by spaces. Known tags are shown with their color and the one
+/// being written is completed with the first known tag starting like it, accepted with the right arrow.
+pub struct TagsInputWidget {
+    input: InputWidget,
+    known_tags: BTreeMap<String, TagColor>,
+}
+
+impl TagsInputWidget {
+    pub fn new(known_tags: BTreeMap<String, TagColor>) -> TagsInputWidget {
+        TagsInputWidget {
+            input: InputWidget::create_text_label(),
+            known_tags,
+        }
+    }
+
+    pub fn get_tags(&self) -> BTreeSet<String> {
+        split_tags(&self.input.get_current_text()).map(String::from).collect()
+    }
+
+    pub fn set_tags(&mut self, tags: &BTreeSet<String>) {
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        self.input.set_text(tags.join(" "));
+    }
+
+    //Rest of the first known tag starting like the tag being written, skipping the ones already given
+    fn get_suggestion(&self) -> Option<&str> {
+        let text = self.input.get_current_text();
+        let written_tag = text.rsplit(is_separator).next().filter(|written_tag| !written_tag.is_empty())?;
+        let given_tags: Vec<&str> = split_tags(&text).collect();
+        self.known_tags
+            .keys()
+            .find(|known_tag| known_tag.starts_with(written_tag) && !given_tags.contains(&known_tag.as_str()))
+            .map(|known_tag| &known_tag[written_tag.len()..])
+    }
+}
+
+impl Widget for TagsInputWidget {
+    fn render(&self, frame: &mut Frame<ApplicationBackend>, area: Rect) {
+        let text = self.input.get_current_text();
+        let mut spans = Vec::new();
+        let mut word_start = 0;
+        for (index, character) in text.char_indices().chain([(text.len(), ' ')]) {
+            if !is_separator(character) {
+                continue;
+            }
+            let word = &text[word_start..index];
+            let color = self.known_tags.get(word).map_or(Color::White, |tag_color| to_color(*tag_color));
+            spans.push(Span::styled(word.to_string(), Style::default().fg(color)));
+            if index < text.len() {
+                spans.push(Span::raw(character.to_string()));
+            }
+            word_start = index + character.len_utf8();
+        }
+        if self.input.get_focus_state() == FocusState::Focused {
+            if let Some(suggestion) = self.get_suggestion() {
+                spans.push(Span::styled(suggestion.to_string(), Style::default().fg(Color::DarkGray)));
+            }
+        }
+        if self.input.is_cursor_showing() {
+            spans.push(Span::raw("|"));
+        }
+        let paragraph = Paragraph::new(Spans::from(spans))
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        frame.render_widget(paragraph, area)
+    }
+}
+
+impl FocusableWidget for TagsInputWidget {
+    fn focus_state_changed(&mut self, focus_state: FocusState) {
+        self.input.focus_state_changed(focus_state);
+    }
+
+    fn get_focus_state(&self) -> FocusState {
+        self.input.get_focus_state()
+    }
+
+    fn process_input(&mut self, key_code: KeyCode) {
+        if key_code != KeyCode::Right {
+            self.input.process_input(key_code);
+            return;
+        }
+        if let Some(suggestion) = self.get_suggestion().map(String::from) {
+            suggestion.chars().chain([' ']).for_each(|character| self.input.process_input(KeyCode::Char(character)));
+        }
+    }
+
+    fn update(&mut self, duration: Duration) {
+        self.input.update(duration);
+    }
+}
+
+pub fn to_color(tag_color: TagColor) -> Color {
+    match tag_color {
+        TagColor::Red => Color::Red,
+        TagColor::Green => Color::Green,
+        TagColor::Yellow => Color::Yellow,
+        TagColor::Blue => Color::Blue,
+        TagColor::Magenta => Color::Magenta,
+        TagColor::Cyan => Color::Cyan,
+        TagColor::Gray => Color::Gray,
+    }
+}
+
+fn is_separator(character: char) -> bool {
+    !tag::is_valid_tag_name(&character.to_string())
+}
+
+fn split_tags(text: &str) -> impl Iterator<Item = &str> {
+    text.split(is_separator).filter(|tag| !tag.is_empty())
+}
@@ -0,0 +1,303 @@
+use serde::Deserialize;
+use std::{
+    fmt,
+    io::{ErrorKind, Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::backend::{TaskEvent, TaskManager};
+
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+const OUTPUT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Executables to run on each task event, from the `[hooks]` table of `tant.toml`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub created: Vec<String>,
+    pub state_changed: Vec<String>,
+    pub deleted: Vec<String>,
+    //Seconds a hook can run before being killed
+    pub timeout: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> HooksConfig {
+        HooksConfig {
+            created: Vec::new(),
+            state_changed: Vec::new(),
+            deleted: Vec::new(),
+            timeout: DEFAULT_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Makes the relative paths of the hooks relative to `directory`.
+    pub fn resolve_paths(&mut self, directory: &Path) {
+        for hook in self.created.iter_mut().chain(self.state_changed.iter_mut()).chain(self.deleted.iter_mut()) {
+            *hook = directory.join(&*hook).to_string_lossy().to_string();
+        }
+    }
+
+    fn hooks_for(&self, event: &TaskEvent) -> &[String] {
+        match event {
+            TaskEvent::Created(_) => &self.created,
+            TaskEvent::StateChanged { .. } => &self.state_changed,
+            TaskEvent::Deleted(_) => &self.deleted,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HookFailure {
+    pub hook: String,
+    pub task_title: String,
+    pub reason: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hook {} failed for \"{}\": {}", self.hook, self.task_title, self.reason)
+    }
+}
+
+enum Message {
+    Event(Box<TaskEvent>),
+    Stop,
+}
+
+/// Runs the hooks in a background thread, one at a time and in the order of the events, so the tasks can
+/// be changed while a slow hook runs.
+pub struct HookRunner {
+    sender: Sender<Message>,
+    failures: Receiver<HookFailure>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HookRunner {
+    /// Failures are printed to stderr when `print_failures` is set, otherwise they are kept for `take_failures`.
+    pub fn start(hooks_config: HooksConfig, print_failures: bool) -> HookRunner {
+        let (sender, events) = mpsc::channel();
+        let (failure_sender, failures) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            while let Ok(Message::Event(event)) = events.recv() {
+                for hook in hooks_config.hooks_for(&event) {
+                    let Err(reason) = run_hook(hook, &event, Duration::from_secs(hooks_config.timeout)) else {
+                        continue;
+                    };
+                    let failure = HookFailure { hook: hook.clone(), task_title: event.task().title.clone(), reason };
+                    if print_failures {
+                        eprintln!("{}", failure);
+                    }
+                    else {
+                        let _ = failure_sender.send(failure);
+                    }
+                }
+            }
+        });
+        HookRunner { sender, failures, worker: Some(worker) }
+    }
+
+    /// Runs the hooks for every event of `task_manager` from now on.
+    pub fn attach(&self, task_manager: &mut TaskManager) {
+        let sender = self.sender.clone();
+        task_manager.set_event_listener(Box::new(move |event| {
+            let _ = sender.send(Message::Event(Box::new(event.clone())));
+        }));
+    }
+
+    /// Hooks that failed since the last call.
+    pub fn take_failures(&self) -> Vec<HookFailure> {
+        self.failures.try_iter().collect()
+    }
+
+    /// Waits for the pending hooks to run, returning the ones that failed.
+    #[cfg(test)]
+    pub fn finish(mut self) -> Vec<HookFailure> {
+        self.stop();
+        self.take_failures()
+    }
+
+    fn stop(&mut self) {
+        let _ = self.sender.send(Message::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for HookRunner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+//The task is written to the standard input of the hook, the event details go in environment variables
+fn run_hook(hook: &str, event: &TaskEvent, timeout: Duration) -> Result<(), String> {
+    let mut command = Command::new(hook);
+    command
+        .env("TANT_EVENT", event.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if let TaskEvent::StateChanged { previous_state, .. } = event {
+        command.env("TANT_PREVIOUS_STATE", previous_state);
+    }
+    let task_json = serde_json::to_string(event.task()).map_err(|error| error.to_string())?;
+    let mut child = command.spawn().map_err(|error| error.to_string())?;
+    let deadline = Instant::now() + timeout;
+
+    //Written in another thread so a hook that does not read the task can not block us past the timeout
+    let mut stdin = child.stdin.take().unwrap();
+    let (input_sender, input_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        //Hooks do not have to read the task
+        let result = match stdin.write_all(task_json.as_bytes()) {
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        };
+        let _ = input_sender.send(result);
+    });
+    //Read in another thread so a hook writing a lot can not block waiting for us
+    let mut stderr = child.stderr.take().unwrap();
+    let (output_sender, output_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        let _ = output_sender.send(output);
+    });
+
+    let status = loop {
+        let error = match (input_receiver.try_recv(), child.try_wait()) {
+            (Ok(Err(error)), _) | (_, Err(error)) => Some(error),
+            (_, Ok(Some(status))) => break Some(status),
+            _ => None,
+        };
+        if error.is_some() || Instant::now() >= deadline {
+            //Killing the hook also ends the write of the task
+            let _ = child.kill();
+            let _ = child.wait();
+            match error {
+                Some(error) => return Err(error.to_string()),
+                None => break None,
+            }
+        }
+        thread::sleep(WAIT_INTERVAL);
+    };
+    //Processes started by the hook in the background may keep stderr open after it exits
+    let output = output_receiver.recv_timeout(OUTPUT_TIMEOUT).unwrap_or_default();
+    match status {
+        Some(status) if status.success() => Ok(()),
+        Some(status) => match output.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(last_line) => Err(format!("{}, {}", status, last_line.trim())),
+            None => Err(status.to_string()),
+        },
+        None => Err(format!("timed out after {} seconds", timeout.as_secs())),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::backend::{storage::MemoryStorage, task::TaskId, DeleteMode};
+    use std::{env, fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+    fn create_hook(directory: &Path, name: &str, script: &str) -> String {
+        let hook_path = directory.join(name);
+        fs::write(&hook_path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755)).unwrap();
+        hook_path.to_string_lossy().to_string()
+    }
+
+    fn create_test_directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("tant-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn hooks_receive_events() {
+        let directory = create_test_directory("hooks-receive-events");
+        let log_path = directory.join("events");
+        let log_hook = create_hook(&directory, "log", &format!("(echo $TANT_EVENT $TANT_PREVIOUS_STATE; cat; echo) >> {}", log_path.display()));
+        let hooks_config = HooksConfig {
+            created: vec![log_hook.clone()],
+            state_changed: vec![log_hook.clone()],
+            deleted: vec![log_hook],
+            ..HooksConfig::default()
+        };
+        let hook_runner = HookRunner::start(hooks_config, false);
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
+        hook_runner.attach(&mut task_manager);
+
+        let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+        assert!(task_manager.set_task_state(task_id, String::from("Done")).is_ok());
+        assert!(task_manager.delete_task(TaskId(0), DeleteMode::DeleteChildren).is_ok());
+        let failures = hook_runner.finish();
+        assert!(failures.is_empty(), "{:?}", failures);
+
+        let log = fs::read_to_string(log_path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "created");
+        assert!(lines[1].starts_with("{\"id\":0,\"title\":\"Title\",\"state\":\"Open\""));
+        assert_eq!(lines[2], "state_changed Open");
+        assert_eq!(lines[4], "deleted");
+    }
+
+    #[test]
+    fn hook_failures() {
+        let directory = create_test_directory("hook-failures");
+        let hooks_config = HooksConfig {
+            created: vec![
+                create_hook(&directory, "fail", "echo broken >&2; exit 3"),
+                create_hook(&directory, "slow", "sleep 5"),
+                directory.join("missing").to_string_lossy().to_string(),
+            ],
+            timeout: 1,
+            ..HooksConfig::default()
+        };
+        let hook_runner = HookRunner::start(hooks_config, false);
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
+        hook_runner.attach(&mut task_manager);
+        assert!(task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).is_ok());
+
+        let failures = hook_runner.finish();
+        assert_eq!(failures.len(), 3);
+        assert!(failures[0].reason.ends_with("exit status: 3, broken"));
+        assert_eq!(failures[1].reason, "timed out after 1 seconds");
+        assert_eq!(failures[2].task_title, "Title");
+    }
+
+    #[test]
+    fn hook_not_reading_a_large_task() {
+        let directory = create_test_directory("hook-not-reading-a-large-task");
+        let hooks_config = HooksConfig {
+            created: vec![create_hook(&directory, "slow", "sleep 5")],
+            timeout: 1,
+            ..HooksConfig::default()
+        };
+        let hook_runner = HookRunner::start(hooks_config, false);
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
+        hook_runner.attach(&mut task_manager);
+        //Bigger than the buffer of the pipe
+        let description = "a".repeat(1 << 20);
+        assert!(task_manager.add_task(String::from("Title"), String::from("Open"), description).is_ok());
+
+        let started = Instant::now();
+        let failures = hook_runner.finish();
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].reason, "timed out after 1 seconds");
+    }
+}
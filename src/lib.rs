@@ -0,0 +1,13 @@
+//! The task model of TAnt and the rules to change it, without the terminal interface.
+//!
+//! ```
+//! use tant::backend::{storage::MemoryStorage, TaskManager};
+//!
+//! let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+//! task_manager.initialize().unwrap();
+//! let task_id = task_manager.add_task(String::from("Title"), String::from("Open"), String::from("")).unwrap();
+//! task_manager.set_task_state(task_id, String::from("Done")).unwrap();
+//! assert_eq!(task_manager.find_task(task_id).unwrap().state, "Done");
+//! ```
+
+pub mod backend;
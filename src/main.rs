@@ -1,15 +1,68 @@
 mod app;
-mod backend;
+mod cli;
+mod config;
 mod frontend;
+mod hooks;
+mod server;
+mod workspace;
 
 
+use tant::backend;
+
 use crate::app::{Application};
-use std::{io, env, rc::Rc, cell::RefCell};
+use crate::backend::{TaskError, TaskManager};
+use crate::cli::Cli;
+use crate::hooks::HookRunner;
+use clap::Parser;
+use std::{io, env, rc::Rc, cell::RefCell, process::ExitCode};
 
-fn main() -> Result<(), io::Error> {
+fn main() -> Result<ExitCode, io::Error> {
     env::set_var("RUST_BACKTRACE", "full");
+    let cli = Cli::parse();
+    let workspace = cli.workspace(&env::current_dir()?);
+    let mut config = match cli.load_config(&workspace) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    config.data_directory = workspace.data_directory();
+    cli.apply_to(&mut config);
+    let storage = match config.create_storage() {
+        Ok(storage) => storage,
+        Err(error) => {
+            eprintln!("{}", error);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let mut task_manager = TaskManager::new(storage);
+    //The interface shows the failures itself, printing them would garble it
+    let hook_runner = HookRunner::start(config.hooks.clone(), cli.command.is_some());
+    hook_runner.attach(&mut task_manager);
+
+    if let Some(command) = cli.command {
+        if let Err(error) = cli::run_command(command, &config, &mut task_manager) {
+            eprintln!("{}", error);
+            return Ok(ExitCode::FAILURE);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let load_error = match task_manager.initialize() {
+        //A corrupted file was already copied aside, and a broken journal leaves the tasks that could be
+        //replayed loaded, so the board can still be shown
+        Err(error @ (TaskError::CorruptedFile { .. } | TaskError::CorruptedJournal { .. })) => Some(error),
+        //Starting with an empty board would overwrite the tasks we could not read
+        Err(error) => {
+            eprintln!("{}", error);
+            return Ok(ExitCode::FAILURE);
+        }
+        Ok(()) => None,
+    };
+
     // setup terminal
-    let app = Rc::new(RefCell::new(Application::new()?));
+    let app = Rc::new(RefCell::new(Application::new(task_manager, workspace, hook_runner, load_error)?));
     app.borrow_mut().run()?;
-    Ok(())
-}
\ No newline at end of file
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,485 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    net::SocketAddr,
+    sync::{Mutex, PoisonError},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::backend::{task::TaskId, DeleteMode, TagColor, TaskError, TaskFields, TaskManager, TaskView};
+
+//Requests are read in parallel, but only one of them changes the tasks at a time
+const WORKER_COUNT: usize = 4;
+
+struct ApiResponse {
+    status: u16,
+    //No body for 204 responses
+    body: Option<Value>,
+}
+
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> ApiError {
+        ApiError { status, message: message.into() }
+    }
+}
+
+impl From<TaskError> for ApiError {
+    fn from(error: TaskError) -> ApiError {
+        let status = match error {
+            TaskError::TaskNotFound(_) | TaskError::TagNotFound(_) => 404,
+            TaskError::ParentNotFound(_)
+            | TaskError::TaskAlreadyExists(_)
+            | TaskError::CyclicParent { .. }
+            | TaskError::InvalidState(_)
+            | TaskError::EmptyStateName
+            | TaskError::StateAlreadyExists(_)
+            | TaskError::InvalidStatePosition(_)
+            | TaskError::InvalidTagName(_)
+            | TaskError::InvalidPriority(_)
+            | TaskError::EmptyPriorityName
+            | TaskError::PriorityAlreadyExists(_)
+            | TaskError::InvalidPriorityPosition(_)
+            | TaskError::StartAfterDue
+            | TaskError::TimerAlreadyRunning(_)
+            | TaskError::TimerNotRunning(_)
+            | TaskError::TransitionNotAllowed { .. }
+            | TaskError::UnfinishedSubtasks { .. } => 422,
+            _ => 500,
+        };
+        ApiError::new(status, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(error: serde_json::Error) -> ApiError {
+        ApiError::new(400, format!("Invalid request body: {}", error))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewTask {
+    title: String,
+    //The first state by default
+    state: Option<String>,
+    #[serde(default)]
+    description: String,
+    parent: Option<u64>,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+    priority: Option<String>,
+    //RFC 3339
+    due_date: Option<DateTime<FixedOffset>>,
+    start_date: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskChanges {
+    title: Option<String>,
+    state: Option<String>,
+    description: Option<String>,
+    //Replaces every tag of the task
+    tags: Option<BTreeSet<String>>,
+    //Missing keeps the priority, null removes it
+    #[serde(default, deserialize_with = "deserialize_present")]
+    priority: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    due_date: Option<Option<DateTime<FixedOffset>>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    start_date: Option<Option<DateTime<FixedOffset>>>,
+    //Missing keeps the parent, null makes it a top level task
+    #[serde(default, deserialize_with = "deserialize_present")]
+    parent: Option<Option<u64>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewState {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StateChanges {
+    name: Option<String>,
+    position: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewPriority {
+    name: String,
+    color: TagColor,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PriorityChanges {
+    name: Option<String>,
+    color: Option<TagColor>,
+    position: Option<usize>,
+}
+
+fn deserialize_present<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Serves the HTTP API until the process is stopped. Every change is saved like in the interface.
+pub fn serve(listen: SocketAddr, task_manager: &mut TaskManager) -> Result<(), Box<dyn Error>> {
+    let server = Server::http(listen).map_err(|error| format!("Could not listen on {}: {}", listen, error))?;
+    if !listen.ip().is_loopback() {
+        eprintln!("Warning: the API has no authentication and {} is reachable from other machines", listen);
+    }
+    println!("Listening on http://{}", listen);
+
+    let task_manager = Mutex::new(task_manager);
+    thread::scope(|scope| {
+        for _ in 0..WORKER_COUNT {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    respond(request, &task_manager);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut request: Request, task_manager: &Mutex<&mut TaskManager>) {
+    let mut body = String::new();
+    let result = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => {
+            //A panic in another request does not leave the tasks half changed, every change is applied at once
+            let mut task_manager = task_manager.lock().unwrap_or_else(PoisonError::into_inner);
+            handle_request(&mut task_manager, request.method(), request.url(), &body)
+        }
+        Err(error) => Err(ApiError::new(400, format!("Could not read the request: {}", error))),
+    };
+    let response = result.unwrap_or_else(|error| ApiResponse {
+        status: error.status,
+        body: Some(json!({ "error": error.message })),
+    });
+
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let body = response.body.map(|body| body.to_string()).unwrap_or_default();
+    let http_response = Response::from_string(body).with_status_code(response.status).with_header(content_type);
+    //The client may be gone already, there is nobody to tell about it
+    let _ = request.respond(http_response);
+}
+
+fn handle_request(task_manager: &mut TaskManager, method: &Method, url: &str, body: &str) -> Result<ApiResponse, ApiError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<String> = path.trim_matches('/').split('/').map(percent_decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let parameters: Vec<(String, String)> = query
+        .split('&')
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(name, value)| (percent_decode(name), percent_decode(value)))
+        .collect();
+    let parameter = |name: &str| parameters.iter().find(|(parameter_name, _)| parameter_name == name).map(|(_, value)| value.as_str());
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["tasks"]) => {
+            let mut task_views = TaskView::list(task_manager);
+            if let Some(tag) = parameter("tag") {
+                task_views.retain(|task_view| task_view.tags.iter().any(|task_tag| task_tag == tag));
+            }
+            Ok(ok(json!(task_views)))
+        }
+        (Method::Post, ["tasks"]) => {
+            let new_task: NewTask = serde_json::from_str(body)?;
+            let state = match new_task.state {
+                Some(state) => state,
+                None => task_manager.get_states().first().cloned().ok_or_else(|| ApiError::new(422, "There are no states"))?,
+            };
+            let fields = TaskFields {
+                title: new_task.title,
+                state,
+                description: new_task.description,
+                tags: new_task.tags,
+                priority: new_task.priority,
+                due_date: new_task.due_date,
+                start_date: new_task.start_date,
+            };
+            let task_id = task_manager.create_task(fields, new_task.parent.map(TaskId))?;
+            Ok(ApiResponse { status: 201, body: Some(task_body(task_manager, task_id)?) })
+        }
+        (Method::Get, ["tasks", id]) => Ok(ok(task_body(task_manager, parse_task_id(id)?)?)),
+        (Method::Patch, ["tasks", id]) => {
+            let task_id = parse_task_id(id)?;
+            let changes: TaskChanges = serde_json::from_str(body)?;
+            let task_fields = task_manager.find_task(task_id).ok_or(TaskError::TaskNotFound(task_id))?.fields();
+            let fields = TaskFields {
+                title: changes.title.unwrap_or_else(|| task_fields.title.clone()),
+                state: changes.state.unwrap_or_else(|| task_fields.state.clone()),
+                description: changes.description.unwrap_or_else(|| task_fields.description.clone()),
+                tags: changes.tags.unwrap_or_else(|| task_fields.tags.clone()),
+                priority: changes.priority.unwrap_or_else(|| task_fields.priority.clone()),
+                due_date: changes.due_date.unwrap_or(task_fields.due_date),
+                start_date: changes.start_date.unwrap_or(task_fields.start_date),
+            };
+            //The move is checked first so a request that fails does not change anything
+            let new_parent = changes.parent.map(|parent| parent.map(TaskId));
+            if let Some(new_parent) = new_parent {
+                task_manager.check_move(task_id, new_parent)?;
+            }
+            if fields != task_fields {
+                task_manager.edit_task(task_id, fields)?;
+            }
+            if let Some(new_parent) = new_parent {
+                task_manager.move_task(task_id, new_parent)?;
+            }
+            Ok(ok(task_body(task_manager, task_id)?))
+        }
+        (Method::Delete, ["tasks", id]) => {
+            let task_id = parse_task_id(id)?;
+            let task = task_manager.find_task(task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+            let delete_mode = match parameter("subtasks") {
+                Some("delete") => DeleteMode::DeleteChildren,
+                Some("reparent") => DeleteMode::ReparentChildren,
+                None if task.child_tasks.is_empty() => DeleteMode::DeleteChildren,
+                None => return Err(ApiError::new(422, "Task has subtasks, add subtasks=delete or subtasks=reparent")),
+                Some(subtasks) => {
+                    let message = format!("Invalid subtasks \"{}\", it can be delete or reparent", subtasks);
+                    return Err(ApiError::new(400, message));
+                }
+            };
+            task_manager.delete_task(task_id, delete_mode)?;
+            Ok(ApiResponse { status: 204, body: None })
+        }
+        (Method::Get, ["states"]) => Ok(ok(json!(task_manager.get_states()))),
+        (Method::Post, ["states"]) => {
+            let new_state: NewState = serde_json::from_str(body)?;
+            task_manager.add_state(new_state.name)?;
+            Ok(ApiResponse { status: 201, body: Some(json!(task_manager.get_states())) })
+        }
+        (Method::Patch, ["states", state]) => {
+            let changes: StateChanges = serde_json::from_str(body)?;
+            let mut state = state.to_string();
+            //Checked before renaming, so a request that fails does not change anything
+            if let Some(position) = changes.position.filter(|position| *position >= task_manager.get_states().len()) {
+                return Err(TaskError::InvalidStatePosition(position).into());
+            }
+            if let Some(new_name) = changes.name {
+                task_manager.rename_state(&state, new_name.clone())?;
+                state = new_name;
+            }
+            if let Some(position) = changes.position {
+                task_manager.move_state(&state, position)?;
+            }
+            Ok(ok(json!(task_manager.get_states())))
+        }
+        (Method::Delete, ["states", state]) => {
+            //Without tasks to move any other state will do
+            let migration_state = match parameter("move_tasks_to") {
+                Some(migration_state) => migration_state.to_string(),
+                None if task_manager.count_tasks_in_state(state) == 0 => task_manager
+                    .get_states()
+                    .iter()
+                    .find(|other_state| other_state != state)
+                    .cloned()
+                    .ok_or_else(|| ApiError::new(422, "The last state can not be deleted"))?,
+                None => return Err(ApiError::new(422, "State has tasks, add move_tasks_to=<state>")),
+            };
+            task_manager.delete_state(state, &migration_state)?;
+            Ok(ApiResponse { status: 204, body: None })
+        }
+        (Method::Get, ["tags"]) => Ok(ok(json!(task_manager.get_tags()))),
+        (Method::Get, ["priorities"]) => Ok(ok(json!(task_manager.get_priorities()))),
+        (Method::Post, ["priorities"]) => {
+            let new_priority: NewPriority = serde_json::from_str(body)?;
+            task_manager.add_priority(new_priority.name, new_priority.color)?;
+            Ok(ApiResponse { status: 201, body: Some(json!(task_manager.get_priorities())) })
+        }
+        (Method::Patch, ["priorities", priority]) => {
+            let changes: PriorityChanges = serde_json::from_str(body)?;
+            let mut priority = priority.to_string();
+            //Checked before renaming, so a request that fails does not change anything
+            if let Some(position) = changes.position.filter(|position| *position >= task_manager.get_priorities().len()) {
+                return Err(TaskError::InvalidPriorityPosition(position).into());
+            }
+            if let Some(new_name) = changes.name {
+                task_manager.rename_priority(&priority, new_name.clone())?;
+                priority = new_name;
+            }
+            if let Some(color) = changes.color {
+                task_manager.set_priority_color(&priority, color)?;
+            }
+            if let Some(position) = changes.position {
+                task_manager.move_priority(&priority, position)?;
+            }
+            Ok(ok(json!(task_manager.get_priorities())))
+        }
+        (Method::Delete, ["priorities", priority]) => {
+            task_manager.delete_priority(priority)?;
+            Ok(ApiResponse { status: 204, body: None })
+        }
+        _ => Err(ApiError::new(404, format!("No endpoint for {} {}", method, path))),
+    }
+}
+
+fn ok(body: Value) -> ApiResponse {
+    ApiResponse { status: 200, body: Some(body) }
+}
+
+fn task_body(task_manager: &TaskManager, task_id: TaskId) -> Result<Value, ApiError> {
+    let task_view = TaskView::find(task_manager, task_id).ok_or(TaskError::TaskNotFound(task_id))?;
+    Ok(json!(task_view))
+}
+
+fn parse_task_id(id: &str) -> Result<TaskId, ApiError> {
+    id.parse().map(TaskId).map_err(|_| ApiError::new(400, format!("\"{}\" is not a task id", id)))
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < text.len() {
+        let byte = text.as_bytes()[index];
+        let escaped_byte = text.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped_byte) {
+            (b'%', Some(escaped_byte)) => {
+                decoded.push(escaped_byte);
+                index += 3;
+            }
+            _ => {
+                decoded.push(if byte == b'+' { b' ' } else { byte });
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::MemoryStorage;
+
+    fn create_task_manager() -> TaskManager {
+        let mut task_manager = TaskManager::new(Box::new(MemoryStorage::new()));
+        task_manager.initialize().unwrap();
+        task_manager
+    }
+
+    fn request(task_manager: &mut TaskManager, method: Method, url: &str, body: &str) -> (u16, Value) {
+        match handle_request(task_manager, &method, url, body) {
+            Ok(response) => (response.status, response.body.unwrap_or(Value::Null)),
+            Err(error) => (error.status, json!({ "error": error.message })),
+        }
+    }
+
+    #[test]
+    fn task_endpoints() {
+        let mut task_manager = create_task_manager();
+        let (status, root) = request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Root", "priority": "High" }"#);
+        assert_eq!(status, 201);
+        assert_eq!((root["state"].as_str(), root["priority"].as_str()), (Some("Open"), Some("High")));
+        let (status, child) = request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Child", "parent": 0 }"#);
+        assert_eq!(status, 201);
+        assert_eq!(child["depth"], 1);
+
+        let (status, child) = request(&mut task_manager, Method::Patch, "/tasks/1", r#"{ "state": "Done", "parent": null }"#);
+        assert_eq!(status, 200);
+        assert_eq!((child["state"].as_str(), child["parent"].as_u64()), (Some("Done"), None));
+        let (status, tasks) = request(&mut task_manager, Method::Get, "/tasks", "");
+        assert_eq!(status, 200);
+        assert_eq!(tasks.as_array().unwrap().len(), 2);
+        let (_, root) = request(&mut task_manager, Method::Patch, "/tasks/0", r#"{ "priority": null }"#);
+        assert_eq!(root["priority"], Value::Null);
+        let (status, root) = request(&mut task_manager, Method::Patch, "/tasks/0", r#"{ "due_date": "2024-05-01T18:00:00+02:00" }"#);
+        assert_eq!(status, 200);
+        assert_eq!(root["due_date"], "2024-05-01T18:00:00+02:00");
+        let dates = r#"{ "start_date": "2024-05-02T00:00:00Z" }"#;
+        assert_eq!(request(&mut task_manager, Method::Patch, "/tasks/0", dates).0, 422);
+
+        assert_eq!(request(&mut task_manager, Method::Delete, "/tasks/1", "").0, 204);
+        assert_eq!(request(&mut task_manager, Method::Get, "/tasks/1", "").0, 404);
+    }
+
+    #[test]
+    fn task_endpoint_errors() {
+        let mut task_manager = create_task_manager();
+        assert_eq!(request(&mut task_manager, Method::Post, "/tasks", r#"{ "name": "Root" }"#).0, 400);
+        assert_eq!(request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Root", "state": "Unknown" }"#).0, 422);
+        assert_eq!(request(&mut task_manager, Method::Get, "/tasks/root", "").0, 400);
+        assert_eq!(request(&mut task_manager, Method::Put, "/tasks", "").0, 404);
+
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Root" }"#);
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Child", "parent": 0 }"#);
+        assert_eq!(request(&mut task_manager, Method::Delete, "/tasks/0", "").0, 422);
+        let (status, error) = request(&mut task_manager, Method::Delete, "/tasks/0?subtasks=keep", "");
+        assert_eq!(status, 400);
+        assert_eq!(error["error"], "Invalid subtasks \"keep\", it can be delete or reparent");
+        //A failed move does not leave the rest of the changes applied
+        assert_eq!(request(&mut task_manager, Method::Patch, "/tasks/0", r#"{ "title": "Renamed", "parent": 1 }"#).0, 422);
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().title, "Root");
+        assert_eq!(request(&mut task_manager, Method::Delete, "/tasks/0?subtasks=reparent", "").0, 204);
+        assert_eq!(task_manager.find_task(TaskId(1)).unwrap().parent_task, None);
+    }
+
+    #[test]
+    fn state_endpoints() {
+        let mut task_manager = create_task_manager();
+        assert_eq!(request(&mut task_manager, Method::Post, "/states", r#"{ "name": "Blocked" }"#).0, 201);
+        let (status, states) = request(&mut task_manager, Method::Patch, "/states/In%20progress", r#"{ "name": "Doing", "position": 0 }"#);
+        assert_eq!(status, 200);
+        assert_eq!(states, json!(["Doing", "Open", "Selected for development", "Done", "Blocked"]));
+        assert_eq!(request(&mut task_manager, Method::Patch, "/states/Blocked", r#"{ "name": "Waiting", "position": 5 }"#).0, 422);
+        assert_eq!(task_manager.get_states()[4], "Blocked");
+
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Task", "state": "Doing" }"#);
+        assert_eq!(request(&mut task_manager, Method::Delete, "/states/Doing", "").0, 422);
+        assert_eq!(request(&mut task_manager, Method::Delete, "/states/Doing?move_tasks_to=Blocked", "").0, 204);
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().state, "Blocked");
+    }
+
+    #[test]
+    fn tag_endpoints() {
+        let mut task_manager = create_task_manager();
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "First", "tags": ["work"] }"#);
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Second" }"#);
+        let (status, task) = request(&mut task_manager, Method::Patch, "/tasks/1", r#"{ "tags": ["home", "work"] }"#);
+        assert_eq!(status, 200);
+        assert_eq!(task["tags"], json!(["home", "work"]));
+        assert_eq!(request(&mut task_manager, Method::Patch, "/tasks/1", r#"{ "tags": ["two words"] }"#).0, 422);
+
+        let (_, tasks) = request(&mut task_manager, Method::Get, "/tasks?tag=home", "");
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+        assert_eq!(tasks[0]["title"], "Second");
+        let (_, tags) = request(&mut task_manager, Method::Get, "/tags", "");
+        assert_eq!(tags, json!({ "home": "green", "work": "red" }));
+    }
+
+    #[test]
+    fn priority_endpoints() {
+        let mut task_manager = create_task_manager();
+        let (status, priorities) = request(&mut task_manager, Method::Post, "/priorities", r#"{ "name": "Urgent", "color": "magenta" }"#);
+        assert_eq!(status, 201);
+        assert_eq!(priorities[3], json!({ "name": "Urgent", "color": "magenta" }));
+        request(&mut task_manager, Method::Post, "/tasks", r#"{ "title": "Task", "priority": "High" }"#);
+
+        let body = r#"{ "name": "Important", "color": "cyan", "position": 1 }"#;
+        let (status, priorities) = request(&mut task_manager, Method::Patch, "/priorities/High", body);
+        assert_eq!(status, 200);
+        assert_eq!(priorities[1], json!({ "name": "Important", "color": "cyan" }));
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().priority.as_deref(), Some("Important"));
+        assert_eq!(request(&mut task_manager, Method::Patch, "/priorities/Low", r#"{ "name": "Later", "position": 4 }"#).0, 422);
+        assert!(task_manager.find_priority("Low").is_some());
+        assert_eq!(request(&mut task_manager, Method::Post, "/priorities", r#"{ "name": "Low", "color": "red" }"#).0, 422);
+
+        assert_eq!(request(&mut task_manager, Method::Delete, "/priorities/Important", "").0, 204);
+        assert_eq!(task_manager.find_task(TaskId(0)).unwrap().priority, None);
+        assert_eq!(request(&mut task_manager, Method::Delete, "/priorities/Important", "").0, 422);
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::config::CONFIG_FILE_NAME;
+
+pub const WORKSPACE_DIRECTORY_NAME: &str = ".tant";
+const APPLICATION_DIRECTORY_NAME: &str = "tant";
+
+/// Where the board and its configuration are stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Workspace {
+    /// The board of a project, in the given `.tant` directory
+    Project(PathBuf),
+    /// The board used outside of any project, in the XDG directories
+    Global,
+}
+
+impl Workspace {
+    /// Looks for a `.tant` directory in `directory` and its ancestors, like git does with `.git`.
+    pub fn discover(directory: &Path) -> Workspace {
+        directory
+            .ancestors()
+            .map(|ancestor| ancestor.join(WORKSPACE_DIRECTORY_NAME))
+            .find(|workspace_directory| workspace_directory.is_dir())
+            .map_or(Workspace::Global, Workspace::Project)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        match self {
+            Workspace::Project(directory) => directory.join(CONFIG_FILE_NAME),
+            Workspace::Global => xdg_directory("XDG_CONFIG_HOME", ".config").join(CONFIG_FILE_NAME),
+        }
+    }
+
+    /// The directory of the tasks file, unless the configuration chooses another one.
+    pub fn data_directory(&self) -> PathBuf {
+        match self {
+            Workspace::Project(directory) => directory.clone(),
+            Workspace::Global => xdg_directory("XDG_DATA_HOME", ".local/share"),
+        }
+    }
+}
+
+impl fmt::Display for Workspace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Workspace::Project(directory) => {
+                let project_directory = directory.parent().unwrap_or(directory);
+                write!(f, "{}", project_directory.display())
+            }
+            Workspace::Global => write!(f, "global"),
+        }
+    }
+}
+
+//Follows the XDG base directory specification, where relative paths in the variables are ignored
+fn xdg_directory(variable: &str, home_fallback: &str) -> PathBuf {
+    let base_directory = env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|directory| directory.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback)))
+        .unwrap_or_default();
+    base_directory.join(APPLICATION_DIRECTORY_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn discover_workspace_in_ancestor() {
+        let directory = env::temp_dir().join(format!("tant-workspace-{}", std::process::id()));
+        let nested_directory = directory.join("src").join("module");
+        fs::create_dir_all(&nested_directory).unwrap();
+        fs::create_dir_all(directory.join(WORKSPACE_DIRECTORY_NAME)).unwrap();
+
+        let workspace = Workspace::discover(&nested_directory);
+        assert_eq!(workspace, Workspace::Project(directory.join(WORKSPACE_DIRECTORY_NAME)));
+        assert_eq!(workspace.config_path(), directory.join(WORKSPACE_DIRECTORY_NAME).join(CONFIG_FILE_NAME));
+        assert_eq!(workspace.to_string(), directory.display().to_string());
+    }
+
+    #[test]
+    fn discover_without_workspace() {
+        assert_eq!(Workspace::discover(Path::new("/")), Workspace::Global);
+    }
+}